@@ -0,0 +1,401 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::cmp::max;
+use std::env;
+use std::error;
+use std::fmt;
+use std::result;
+use crate::i18n::gettext;
+
+/// A description of a command-line option.
+///
+/// An optional argument can be given only after `=` in a long option or directly after a short
+/// option.
+pub struct OptionDesc
+{
+    pub short_name: Option<char>,
+    pub long_name: Option<&'static str>,
+    pub arg_name: Option<&'static str>,
+    pub is_arg_optional: bool,
+    pub description: &'static str,
+}
+
+/// A name of a parsed option.
+///
+/// An option that has a short name is always reported by the short name, even if it is given by
+/// the long name.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OptName
+{
+    Short(char),
+    Long(&'static str),
+}
+
+/// A parsed option with an optional argument.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Opt(pub OptName, pub Option<String>);
+
+/// An error of option parsing.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Error
+{
+    UnknownShortOption(char),
+    UnknownLongOption(String),
+    AmbiguousLongOption(String),
+    MissingShortArgument(char),
+    MissingLongArgument(String),
+    UnexpectedLongArgument(String),
+}
+
+impl error::Error for Error
+{}
+
+impl fmt::Display for Error
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            Error::UnknownShortOption(c)       => write!(f, "{} -- {:?}", gettext("unknown option"), c),
+            Error::UnknownLongOption(name)     => write!(f, "{} -- {:?}", gettext("unknown option"), name),
+            Error::AmbiguousLongOption(name)   => write!(f, "{} -- {:?}", gettext("option is ambiguous"), name),
+            Error::MissingShortArgument(c)     => write!(f, "{} -- {:?}", gettext("option requires an argument"), c),
+            Error::MissingLongArgument(name)   => write!(f, "{} -- {:?}", gettext("option requires an argument"), name),
+            Error::UnexpectedLongArgument(name) => write!(f, "{} -- {:?}", gettext("option doesn't allow an argument"), name),
+        }
+    }
+}
+
+/// A parser of command-line options in the GNU style.
+///
+/// The parser supports clustered short options, long options with `=` or with a separate
+/// argument, and unambiguous abbreviations of long options. Options and operands can be mixed
+/// unless the `POSIXLY_CORRECT` environment variable is set. The `--` argument terminates
+/// options.
+pub struct Parser<'a>
+{
+    args: &'a [String],
+    opt_descs: &'static [OptionDesc],
+    index: usize,
+    char_index: usize,
+    operands: Vec<String>,
+    is_permuted: bool,
+}
+
+impl<'a> Parser<'a>
+{
+    /// Creates a parser for the arguments. The first argument is a program name and it is skipped.
+    pub fn new(args: &'a [String], opt_descs: &'static [OptionDesc]) -> Parser<'a>
+    {
+        Parser {
+            args,
+            opt_descs,
+            index: 1,
+            char_index: 0,
+            operands: Vec::new(),
+            is_permuted: env::var_os("POSIXLY_CORRECT").is_none(),
+        }
+    }
+
+    /// Returns the operands. This method should be called after parsing all options.
+    pub fn operands(&self) -> &[String]
+    {
+        self.operands.as_slice()
+    }
+
+    fn find_long_option(&self, name: &str) -> result::Result<&'static OptionDesc, Error>
+    {
+        if let Some(opt_desc) = self.opt_descs.iter().find(|opt_desc| opt_desc.long_name == Some(name)) {
+            return Ok(opt_desc);
+        }
+        let mut candidates = self.opt_descs.iter().filter(|opt_desc| opt_desc.long_name.map(|long_name| long_name.starts_with(name)).unwrap_or(false));
+        match (candidates.next(), candidates.next()) {
+            (Some(opt_desc), None) => Ok(opt_desc),
+            (Some(_), Some(_))     => Err(Error::AmbiguousLongOption(String::from(name))),
+            (None, _)              => Err(Error::UnknownLongOption(String::from(name))),
+        }
+    }
+
+    fn opt_name(opt_desc: &OptionDesc) -> OptName
+    {
+        match (opt_desc.short_name, opt_desc.long_name) {
+            (Some(short_name), _) => OptName::Short(short_name),
+            (None, Some(long_name)) => OptName::Long(long_name),
+            (None, None) => panic!("option without name"),
+        }
+    }
+
+    fn next_long_option(&mut self, name_and_value: &'a str) -> result::Result<Opt, Error>
+    {
+        self.index += 1;
+        let (name, value) = match name_and_value.split_once('=') {
+            Some((name, value)) => (name, Some(String::from(value))),
+            None                => (name_and_value, None),
+        };
+        let opt_desc = self.find_long_option(name)?;
+        let long_name = opt_desc.long_name.unwrap();
+        if opt_desc.arg_name.is_some() {
+            match value {
+                Some(value) => Ok(Opt(Self::opt_name(opt_desc), Some(value))),
+                None if opt_desc.is_arg_optional => Ok(Opt(Self::opt_name(opt_desc), None)),
+                None => {
+                    match self.args.get(self.index) {
+                        Some(value) => {
+                            self.index += 1;
+                            Ok(Opt(Self::opt_name(opt_desc), Some(value.clone())))
+                        },
+                        None => Err(Error::MissingLongArgument(String::from(long_name))),
+                    }
+                },
+            }
+        } else if value.is_some() {
+            Err(Error::UnexpectedLongArgument(String::from(long_name)))
+        } else {
+            Ok(Opt(Self::opt_name(opt_desc), None))
+        }
+    }
+
+    fn next_short_option(&mut self, arg: &'a str) -> result::Result<Opt, Error>
+    {
+        let c = arg[self.char_index..].chars().next().unwrap();
+        self.char_index += c.len_utf8();
+        let is_last_char = self.char_index >= arg.len();
+        let opt_desc = self.opt_descs.iter().find(|opt_desc| opt_desc.short_name == Some(c));
+        match opt_desc {
+            Some(opt_desc) if opt_desc.arg_name.is_some() && opt_desc.is_arg_optional => {
+                let value = if !is_last_char { Some(String::from(&arg[self.char_index..])) } else { None };
+                self.index += 1;
+                self.char_index = 0;
+                Ok(Opt(OptName::Short(c), value))
+            },
+            Some(opt_desc) if opt_desc.arg_name.is_some() => {
+                let value = if !is_last_char {
+                    String::from(&arg[self.char_index..])
+                } else {
+                    match self.args.get(self.index + 1) {
+                        Some(value) => {
+                            self.index += 1;
+                            value.clone()
+                        },
+                        None => {
+                            self.index += 1;
+                            self.char_index = 0;
+                            return Err(Error::MissingShortArgument(c));
+                        },
+                    }
+                };
+                self.index += 1;
+                self.char_index = 0;
+                Ok(Opt(OptName::Short(c), Some(value)))
+            },
+            Some(_) => {
+                if is_last_char {
+                    self.index += 1;
+                    self.char_index = 0;
+                }
+                Ok(Opt(OptName::Short(c), None))
+            },
+            None => {
+                if is_last_char {
+                    self.index += 1;
+                    self.char_index = 0;
+                }
+                Err(Error::UnknownShortOption(c))
+            },
+        }
+    }
+}
+
+impl<'a> Iterator for Parser<'a>
+{
+    type Item = result::Result<Opt, Error>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        loop {
+            let arg: &'a str = self.args.get(self.index)?.as_str();
+            if self.char_index > 0 {
+                return Some(self.next_short_option(arg));
+            }
+            if arg == "--" {
+                self.operands.extend(self.args[(self.index + 1)..].iter().cloned());
+                self.index = self.args.len();
+                return None;
+            }
+            if let Some(name_and_value) = arg.strip_prefix("--") {
+                return Some(self.next_long_option(name_and_value));
+            }
+            if arg.starts_with('-') && arg != "-" {
+                self.char_index = 1;
+                return Some(self.next_short_option(arg));
+            }
+            if !self.is_permuted {
+                self.operands.extend(self.args[self.index..].iter().cloned());
+                self.index = self.args.len();
+                return None;
+            }
+            self.operands.push(String::from(arg));
+            self.index += 1;
+        }
+    }
+}
+
+/// Prints the descriptions of the options as a help text.
+pub fn print_option_help(opt_descs: &[OptionDesc])
+{
+    let mut opt_strings: Vec<String> = Vec::new();
+    for opt_desc in opt_descs.iter() {
+        let mut opt_string = match opt_desc.short_name {
+            Some(short_name) => format!("-{}", short_name),
+            None             => String::from("  "),
+        };
+        match (opt_desc.short_name, opt_desc.long_name, opt_desc.arg_name) {
+            (_, Some(long_name), Some(arg_name)) if opt_desc.is_arg_optional => {
+                opt_string.push_str(if opt_desc.short_name.is_some() { ", " } else { "  " });
+                opt_string.push_str(format!("--{}[={}]", long_name, arg_name).as_str());
+            },
+            (_, Some(long_name), Some(arg_name)) => {
+                opt_string.push_str(if opt_desc.short_name.is_some() { ", " } else { "  " });
+                opt_string.push_str(format!("--{}={}", long_name, arg_name).as_str());
+            },
+            (_, Some(long_name), None) => {
+                opt_string.push_str(if opt_desc.short_name.is_some() { ", " } else { "  " });
+                opt_string.push_str(format!("--{}", long_name).as_str());
+            },
+            (Some(_), None, Some(arg_name)) if opt_desc.is_arg_optional => opt_string.push_str(format!("[{}]", arg_name).as_str()),
+            (Some(_), None, Some(arg_name)) => opt_string.push_str(format!(" {}", arg_name).as_str()),
+            (_, None, _) => (),
+        }
+        opt_strings.push(opt_string);
+    }
+    let max_opt_string_len = opt_strings.iter().fold(0, |x, opt_string| max(x, opt_string.chars().count()));
+    for (opt_string, opt_desc) in opt_strings.iter().zip(opt_descs.iter()) {
+        println!("  {:<width$}  {}", opt_string, opt_desc.description, width = max_opt_string_len);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const TEST_OPTIONS: &[OptionDesc] = &[
+        OptionDesc { short_name: Some('a'), long_name: Some("all"), arg_name: None, is_arg_optional: false, description: "", },
+        OptionDesc { short_name: Some('B'), long_name: Some("block-size"), arg_name: Some("SIZE"), is_arg_optional: false, description: "", },
+        OptionDesc { short_name: Some('h'), long_name: Some("human-readable"), arg_name: None, is_arg_optional: false, description: "", },
+        OptionDesc { short_name: None, long_name: Some("html"), arg_name: None, is_arg_optional: false, description: "", },
+        OptionDesc { short_name: None, long_name: Some("output"), arg_name: Some("FIELDS"), is_arg_optional: true, description: "", },
+        OptionDesc { short_name: Some('T'), long_name: Some("print-type"), arg_name: None, is_arg_optional: false, description: "", },
+        OptionDesc { short_name: Some('t'), long_name: Some("type"), arg_name: Some("TYPE"), is_arg_optional: false, description: "", },
+        OptionDesc { short_name: None, long_name: Some("total"), arg_name: None, is_arg_optional: false, description: "", },
+        OptionDesc { short_name: None, long_name: Some("help"), arg_name: None, is_arg_optional: false, description: "", },
+    ];
+
+    fn parse(args: &[&str], is_permuted: bool) -> (Vec<result::Result<Opt, Error>>, Vec<String>)
+    {
+        let mut args: Vec<String> = args.iter().map(|arg| String::from(*arg)).collect();
+        args.insert(0, String::from("mntdf"));
+        let mut parser = Parser::new(args.as_slice(), TEST_OPTIONS);
+        // The mode is set explicitly so that tests don't depend on `POSIXLY_CORRECT`.
+        parser.is_permuted = is_permuted;
+        let opts: Vec<result::Result<Opt, Error>> = parser.by_ref().collect();
+        (opts, parser.operands().to_vec())
+    }
+
+    fn short(c: char, value: Option<&str>) -> result::Result<Opt, Error>
+    {
+        Ok(Opt(OptName::Short(c), value.map(String::from)))
+    }
+
+    fn long(name: &'static str, value: Option<&str>) -> result::Result<Opt, Error>
+    {
+        Ok(Opt(OptName::Long(name), value.map(String::from)))
+    }
+
+    #[test]
+    fn test_parser_parses_clustered_short_options()
+    {
+        assert_eq!((vec![short('h', None), short('T', None), short('a', None)], Vec::new()), parse(&["-hTa"], true));
+        assert_eq!((vec![short('h', None), short('B', Some("1K"))], Vec::new()), parse(&["-hB1K"], true));
+    }
+
+    #[test]
+    fn test_parser_parses_short_arguments()
+    {
+        assert_eq!((vec![short('B', Some("1K"))], Vec::new()), parse(&["-B1K"], true));
+        assert_eq!((vec![short('B', Some("1K"))], Vec::new()), parse(&["-B", "1K"], true));
+        assert_eq!((vec![short('t', Some("-a"))], Vec::new()), parse(&["-t", "-a"], true));
+    }
+
+    #[test]
+    fn test_parser_parses_long_arguments()
+    {
+        assert_eq!((vec![short('B', Some("1K"))], Vec::new()), parse(&["--block-size=1K"], true));
+        assert_eq!((vec![short('B', Some("1K"))], Vec::new()), parse(&["--block-size", "1K"], true));
+        assert_eq!((vec![short('B', Some(""))], Vec::new()), parse(&["--block-size="], true));
+        assert_eq!((vec![long("output", Some("size"))], Vec::new()), parse(&["--output=size"], true));
+        assert_eq!((vec![long("output", None)], vec![String::from("size")]), parse(&["--output", "size"], true));
+        assert_eq!((vec![long("total", None)], Vec::new()), parse(&["--total"], true));
+    }
+
+    #[test]
+    fn test_parser_parses_long_option_prefixes()
+    {
+        assert_eq!((vec![short('B', Some("1K"))], Vec::new()), parse(&["--block=1K"], true));
+        assert_eq!((vec![long("help", None)], Vec::new()), parse(&["--he"], true));
+        assert_eq!((vec![short('T', None)], Vec::new()), parse(&["--p"], true));
+        assert_eq!((vec![Err(Error::AmbiguousLongOption(String::from("h")))], Vec::new()), parse(&["--h"], true));
+        assert_eq!((vec![Err(Error::AmbiguousLongOption(String::from("t")))], Vec::new()), parse(&["--t"], true));
+        assert_eq!((vec![short('t', Some("ext4"))], Vec::new()), parse(&["--type", "ext4"], true));
+    }
+
+    #[test]
+    fn test_parser_parses_operands()
+    {
+        assert_eq!((vec![short('a', None), short('T', None)], vec![String::from("/"), String::from("/home")]), parse(&["/", "-a", "/home", "-T"], true));
+        assert_eq!((vec![short('a', None)], vec![String::from("-"), String::from("/")]), parse(&["-", "-a", "/"], true));
+        assert_eq!((vec![short('a', None)], vec![String::from("-T"), String::from("--total")]), parse(&["-a", "--", "-T", "--total"], true));
+    }
+
+    #[test]
+    fn test_parser_does_not_permute_arguments()
+    {
+        assert_eq!((vec![short('a', None)], vec![String::from("/"), String::from("-T")]), parse(&["-a", "/", "-T"], false));
+        assert_eq!((Vec::new(), vec![String::from("-"), String::from("-a")]), parse(&["-", "-a"], false));
+    }
+
+    #[test]
+    fn test_parser_checks_posixly_correct()
+    {
+        let args = vec![String::from("mntdf")];
+        env::set_var("POSIXLY_CORRECT", "1");
+        let is_permuted = Parser::new(args.as_slice(), TEST_OPTIONS).is_permuted;
+        env::remove_var("POSIXLY_CORRECT");
+        assert!(!is_permuted);
+        assert!(Parser::new(args.as_slice(), TEST_OPTIONS).is_permuted);
+    }
+
+    #[test]
+    fn test_parser_reports_errors()
+    {
+        assert_eq!((vec![Err(Error::MissingShortArgument('B'))], Vec::new()), parse(&["-B"], true));
+        assert_eq!((vec![short('h', None), Err(Error::MissingShortArgument('B'))], Vec::new()), parse(&["-hB"], true));
+        assert_eq!((vec![Err(Error::MissingLongArgument(String::from("block-size")))], Vec::new()), parse(&["--block-size"], true));
+        assert_eq!((vec![Err(Error::UnexpectedLongArgument(String::from("total")))], Vec::new()), parse(&["--total=1"], true));
+        assert_eq!((vec![Err(Error::UnknownShortOption('x')), short('a', None)], Vec::new()), parse(&["-xa"], true));
+        assert_eq!((vec![Err(Error::UnknownLongOption(String::from("foo")))], Vec::new()), parse(&["--foo"], true));
+    }
+}
@@ -0,0 +1,151 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io::*;
+use std::path::*;
+use crate::args::{Opt, OptionDesc, Parser, print_option_help};
+use crate::args::OptName::*;
+use crate::i18n::gettext;
+use crate::mountinfo::unescape_mount_path;
+
+const CHECK_OPTIONS: &[OptionDesc] = &[
+    OptionDesc { short_name: None, long_name: Some("fstab"), arg_name: Some("FILE"), is_arg_optional: false, description: "read filesystems from FILE instead of /etc/fstab", },
+    OptionDesc { short_name: None, long_name: Some("mtab"), arg_name: Some("FILE"), is_arg_optional: false, description: "read mount entries from FILE instead of /proc/mounts", },
+    OptionDesc { short_name: None, long_name: Some("help"), arg_name: None, is_arg_optional: false, description: "display this help and exit", },
+];
+
+struct FstabEntry
+{
+    spec: String,
+    file: PathBuf,
+    vfstype: String,
+    options: Vec<String>,
+}
+
+impl FstabEntry
+{
+    fn is_mountable(&self) -> bool
+    {
+        self.vfstype != "swap" && self.file.is_absolute()
+    }
+
+    fn is_mandatory(&self) -> bool
+    {
+        !self.options.iter().any(|option| option == "noauto" || option == "nofail")
+    }
+}
+
+fn read_fstab<P: AsRef<Path>>(path: P) -> Result<Vec<FstabEntry>>
+{
+    let file = fs::File::open(path)?;
+    let mut entries: Vec<FstabEntry> = Vec::new();
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Invalid fstab line {}", line_no + 1)));
+        }
+        entries.push(FstabEntry {
+                spec: unescape_mount_path(fields[0]),
+                file: PathBuf::from(unescape_mount_path(fields[1])),
+                vfstype: String::from(fields[2]),
+                options: fields[3].split(',').map(String::from).collect(),
+        });
+    }
+    Ok(entries)
+}
+
+fn print_check_help(program_name: &str)
+{
+    println!("Usage: {} check [OPTION]...", program_name);
+    println!("Check whether filesystems from fstab are mounted and whether mounted filesystems");
+    println!("are in fstab. Exit with status 1 if a mandatory filesystem isn't mounted.");
+    println!();
+    println!("Options:");
+    print_option_help(CHECK_OPTIONS);
+}
+
+/// Runs the `check` subcommand and returns an exit status. The first argument should be the
+/// subcommand name.
+pub fn check_main(program_name: &str, args: &[String]) -> i32
+{
+    let mut opt_parser = Parser::new(args, CHECK_OPTIONS);
+    let mut fstab = PathBuf::from("/etc/fstab");
+    let mut mtab = PathBuf::from(crate::PROC_MOUNTS);
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt(Long("fstab"), Some(opt_arg)))) => fstab = PathBuf::from(opt_arg),
+            Some(Ok(Opt(Long("mtab"), Some(opt_arg)))) => mtab = PathBuf::from(opt_arg),
+            Some(Ok(Opt(Long("help"), _))) => {
+                print_check_help(program_name);
+                return 0;
+            },
+            Some(Ok(Opt(name, _))) => {
+                eprintln!("{} -- {:?}", gettext("unknown option"), name);
+                return 1;
+            },
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    if !opt_parser.operands().is_empty() {
+        eprintln!("{}", gettext("too many arguments"));
+        return 1;
+    }
+    let fstab_entries = match read_fstab(fstab.as_path()) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("{}: {}", fstab.to_string_lossy(), err);
+            return 1;
+        },
+    };
+    let mount_entries = match crate::get_mounts(mtab.as_path()) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 1;
+        },
+    };
+    let mount_points: Vec<PathBuf> = mount_entries.iter().map(|entry| PathBuf::from(unescape_mount_path(&entry.file.to_string_lossy()))).collect();
+    let mut status = 0;
+    for fstab_entry in fstab_entries.iter().filter(|entry| entry.is_mountable()) {
+        if !mount_points.contains(&fstab_entry.file) {
+            if fstab_entry.is_mandatory() {
+                println!("{}: {} ({})", fstab_entry.file.to_string_lossy(), gettext("not mounted"), fstab_entry.spec);
+                status = 1;
+            } else {
+                println!("{}: {} ({})", fstab_entry.file.to_string_lossy(), gettext("not mounted, optional"), fstab_entry.spec);
+            }
+        }
+    }
+    for (mount_entry, mount_point) in mount_entries.iter().zip(mount_points.iter()) {
+        // Only filesystems that can be in fstab are checked, that is filesystems on devices and
+        // remote filesystems.
+        let is_checked = mount_entry.spec.starts_with('/') || crate::is_remote_mount_entry(mount_entry);
+        if is_checked && !fstab_entries.iter().any(|entry| entry.file == *mount_point) {
+            println!("{}: {} ({})", mount_point.to_string_lossy(), gettext("mounted but not in fstab"), mount_entry.spec);
+        }
+    }
+    status
+}
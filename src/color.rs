@@ -0,0 +1,98 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use std::result;
+
+/// A mode of coloring the output.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorMode
+{
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode
+{
+    /// Returns the color mode that has the specified name. Names of `ls --color` are accepted.
+    pub fn from_name(name: &str) -> Option<ColorMode>
+    {
+        match name {
+            "auto" | "tty" | "if-tty"  => Some(ColorMode::Auto),
+            "always" | "yes" | "force" => Some(ColorMode::Always),
+            "never" | "no" | "none"    => Some(ColorMode::Never),
+            _                          => None,
+        }
+    }
+
+    /// Returns `true` if the output should be colored. In the auto mode, the output is colored
+    /// if the standard output is a terminal and the `NO_COLOR` environment variable isn't set.
+    pub fn is_colored(self) -> bool
+    {
+        match self {
+            ColorMode::Auto   => (unsafe { libc::isatty(libc::STDOUT_FILENO) } == 1) && env::var_os("NO_COLOR").is_none(),
+            ColorMode::Always => true,
+            ColorMode::Never  => false,
+        }
+    }
+}
+
+const STYLE_WORDS: &[(&str, &str)] = &[
+    ("bold", "1"),
+    ("dim", "2"),
+    ("italic", "3"),
+    ("underline", "4"),
+    ("blink", "5"),
+    ("reverse", "7"),
+    ("black", "30"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+    ("white", "37"),
+    ("bright-black", "90"),
+    ("bright-red", "91"),
+    ("bright-green", "92"),
+    ("bright-yellow", "93"),
+    ("bright-blue", "94"),
+    ("bright-magenta", "95"),
+    ("bright-cyan", "96"),
+    ("bright-white", "97"),
+];
+
+/// Parses a style such as `bold red` to SGR parameters. A style is a list of words which are
+/// separated by spaces. Numeric SGR parameters are also accepted as words.
+pub fn parse_style(s: &str) -> result::Result<String, String>
+{
+    let mut sgrs: Vec<String> = Vec::new();
+    for word in s.split_whitespace() {
+        match STYLE_WORDS.iter().find(|pair| pair.0 == word) {
+            Some((_, sgr)) => sgrs.push(String::from(*sgr)),
+            None if !word.is_empty() && word.split(';').all(|x| !x.is_empty() && x.chars().all(|c| c.is_ascii_digit())) => sgrs.push(String::from(word)),
+            None => return Err(format!("style {:?} unknown", word)),
+        }
+    }
+    Ok(sgrs.join(";"))
+}
+
+/// Surrounds a string with the escape sequences which set and reset SGR parameters.
+pub fn colorize(s: &str, sgr: &str) -> String
+{
+    format!("\x1b[{}m{}\x1b[0m", sgr, s)
+}
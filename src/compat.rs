@@ -0,0 +1,134 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use crate::field::*;
+
+/// An output dialect of a df implementation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Compat
+{
+    Gnu,
+    Posix,
+    Busybox,
+}
+
+/// Headers and defaults of an output dialect.
+pub struct Dialect
+{
+    /// The block size if a block size isn't specified.
+    pub default_block_size: u64,
+    /// If `true`, the block size in the size header is printed as a number of bytes, for example
+    /// `1024-blocks` instead of `1K-blocks`. This applies only to default block sizes.
+    pub is_numeric_block_size: bool,
+    /// The headers that differ from the default headers.
+    header_overrides: &'static [(Field, &'static str)],
+}
+
+const DEFAULT_HEADERS: &[(Field, &str)] = &[
+    (Field::Source, "Filesystem"),
+    (Field::FsType, "Type"),
+    (Field::ITotal, "Inodes"),
+    (Field::IUsed, "IUsed"),
+    (Field::IAvail, "IFree"),
+    (Field::IPcent, "IUse%"),
+    (Field::Size, "Size"),
+    (Field::Used, "Used"),
+    (Field::Avail, "Avail"),
+    (Field::Pcent, "Use%"),
+    (Field::Target, "Mounted on"),
+    (Field::File, "File"),
+    (Field::Reserved, "Reserved"),
+    (Field::Options, "Options"),
+    (Field::Flags, "Flags"),
+    (Field::Fsid, "FSID"),
+    (Field::NameMax, "NameMax"),
+    (Field::RawFlags, "RawFlags"),
+    (Field::MajMin, "MAJ:MIN"),
+    (Field::Uuid, "UUID"),
+    (Field::Label, "Label"),
+    (Field::Backing, "Backing file"),
+    (Field::UpperDir, "Upper dir"),
+    (Field::LowerDir, "Lower dirs"),
+    (Field::Server, "Server"),
+    (Field::Export, "Export"),
+    (Field::Propagation, "Propagation"),
+    (Field::Id, "ID"),
+    (Field::Parent, "Parent"),
+    (Field::Subvol, "Subvolume"),
+    (Field::SubvolId, "Subvol ID"),
+    (Field::Bar, "Usage"),
+    (Field::FullIn, "Full in"),
+];
+
+const GNU_DIALECT: Dialect = Dialect {
+    default_block_size: 1024,
+    is_numeric_block_size: false,
+    header_overrides: &[],
+};
+
+const POSIX_DIALECT: Dialect = Dialect {
+    default_block_size: 512,
+    is_numeric_block_size: true,
+    header_overrides: &[
+        (Field::Avail, "Available"),
+        (Field::Pcent, "Capacity"),
+    ],
+};
+
+const BUSYBOX_DIALECT: Dialect = Dialect {
+    default_block_size: 1024,
+    is_numeric_block_size: false,
+    header_overrides: &[
+        (Field::IUsed, "Used"),
+        (Field::IAvail, "Available"),
+        (Field::IPcent, "Use%"),
+        (Field::Avail, "Available"),
+    ],
+};
+
+impl Compat
+{
+    /// Returns the compatibility mode that has the specified name.
+    pub fn from_name(name: &str) -> Option<Compat>
+    {
+        match name {
+            "gnu"     => Some(Compat::Gnu),
+            "posix"   => Some(Compat::Posix),
+            "busybox" => Some(Compat::Busybox),
+            _         => None,
+        }
+    }
+
+    /// Returns the dialect of the compatibility mode.
+    pub fn dialect(&self) -> &'static Dialect
+    {
+        match self {
+            Compat::Gnu     => &GNU_DIALECT,
+            Compat::Posix   => &POSIX_DIALECT,
+            Compat::Busybox => &BUSYBOX_DIALECT,
+        }
+    }
+}
+
+impl Dialect
+{
+    /// Returns the header of the field. The header of the size field is used for human-readable
+    /// sizes.
+    pub fn header(&self, field: Field) -> &'static str
+    {
+        self.header_overrides.iter().chain(DEFAULT_HEADERS.iter()).find(|pair| pair.0 == field).map(|pair| pair.1).unwrap_or("")
+    }
+}
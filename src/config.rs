@@ -0,0 +1,79 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use std::fs;
+use std::io::*;
+use std::path::*;
+use crate::toml::*;
+
+/// Returns the path of a configuration file in the `mntdf` configuration directory.
+///
+/// The directory is `$XDG_CONFIG_HOME/mntdf` or `$HOME/.config/mntdf` if `XDG_CONFIG_HOME` isn't
+/// set. `None` is returned if neither of the variables is set.
+pub fn config_path(name: &str) -> Option<PathBuf>
+{
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _                            => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("mntdf").join(name))
+}
+
+/// Returns the path of a data file in the `mntdf` data directory.
+///
+/// The directory is `$XDG_DATA_HOME/mntdf` or `$HOME/.local/share/mntdf` if `XDG_DATA_HOME` isn't
+/// set. `None` is returned if neither of the variables is set.
+#[cfg(feature = "sqlite")]
+pub fn data_path(name: &str) -> Option<PathBuf>
+{
+    let data_dir = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _                            => PathBuf::from(env::var_os("HOME")?).join(".local").join("share"),
+    };
+    Some(data_dir.join("mntdf").join(name))
+}
+
+/// Reads a list from a configuration file, one item per line. Empty lines and lines starting with
+/// `#` are skipped. `Ok(None)` is returned if the file doesn't exist.
+pub fn read_config_list<P: AsRef<Path>>(path: P) -> Result<Option<Vec<String>>>
+{
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let mut items: Vec<String> = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            items.push(String::from(line));
+        }
+    }
+    Ok(Some(items))
+}
+
+/// Reads a TOML configuration file. `Ok(None)` is returned if the file doesn't exist.
+pub fn read_config_toml<P: AsRef<Path>>(path: P) -> Result<Option<TomlDocument>>
+{
+    let s = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    parse_toml(s.as_str()).map(Some).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
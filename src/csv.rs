@@ -0,0 +1,74 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+/// Quotes a CSV field according to RFC 4180 if the field contains a comma, a quote or a line
+/// break. Fields with spaces are also quoted so that leading and trailing spaces are kept.
+pub fn csv_field(s: &str) -> String
+{
+    if s.contains(&[',', '"', ' ', '\r', '\n'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        String::from(s)
+    }
+}
+
+/// Formats fields as a CSV record without a line terminator.
+pub fn csv_record<S: AsRef<str>>(fields: &[S]) -> String
+{
+    let quoted_fields: Vec<String> = fields.iter().map(|field| csv_field(field.as_ref())).collect();
+    quoted_fields.join(",")
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_csv_field_keeps_plain_fields()
+    {
+        assert_eq!("", csv_field(""));
+        assert_eq!("/dev/sda1", csv_field("/dev/sda1"));
+        assert_eq!("50%", csv_field("50%"));
+    }
+
+    #[test]
+    fn test_csv_field_quotes_special_characters()
+    {
+        assert_eq!("\"a,b\"", csv_field("a,b"));
+        assert_eq!("\"Mounted on\"", csv_field("Mounted on"));
+        assert_eq!("\" a \"", csv_field(" a "));
+        assert_eq!("\"a\nb\"", csv_field("a\nb"));
+        assert_eq!("\"a\rb\"", csv_field("a\rb"));
+    }
+
+    #[test]
+    fn test_csv_field_escapes_quotes()
+    {
+        assert_eq!("\"a\"\"b\"", csv_field("a\"b"));
+        assert_eq!("\"\"\"\"\"\"", csv_field("\"\""));
+        assert_eq!("\"\"\",\"\"\"", csv_field("\",\""));
+    }
+
+    #[test]
+    fn test_csv_record_joins_quoted_fields()
+    {
+        assert_eq!("", csv_record::<&str>(&[]));
+        assert_eq!("tmpfs,\"a \"\"b\"\"\",/tmp", csv_record(&["tmpfs", "a \"b\"", "/tmp"]));
+        assert_eq!(",", csv_record(&[String::new(), String::new()]));
+    }
+}
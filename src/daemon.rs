@@ -0,0 +1,699 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::env;
+use std::fs;
+use std::io::*;
+use std::net::*;
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::RawFd;
+use std::os::unix::net::*;
+use std::path::*;
+use std::str;
+use std::time::*;
+use crate::args::{Opt, OptionDesc, Parser, print_option_help};
+use crate::args::OptName::*;
+#[cfg(feature = "dbus")]
+use crate::dbus::*;
+use crate::field::*;
+use crate::growth::UsageSamples;
+use crate::i18n::{gettext, gettext_fmt};
+use crate::json::*;
+use crate::nagios::PluginState;
+use crate::syslog::LogTarget;
+
+const DAEMON_OPTIONS: &[OptionDesc] = &[
+    OptionDesc { short_name: Some('a'), long_name: Some("all"), arg_name: None, is_arg_optional: false, description: "include filesystems that have zero blocks", },
+    OptionDesc { short_name: Some('c'), long_name: Some("critical"), arg_name: Some("PERCENT"), is_arg_optional: false, description: "report a critical state for capacities of at least PERCENT", },
+    #[cfg(feature = "dbus")]
+    OptionDesc { short_name: None, long_name: Some("dbus"), arg_name: Some("BUS"), is_arg_optional: false, description: "also serve usage over D-Bus on BUS (session or system)", },
+    OptionDesc { short_name: Some('l'), long_name: Some("local"), arg_name: None, is_arg_optional: false, description: "include only local filesystems", },
+    OptionDesc { short_name: None, long_name: Some("listen"), arg_name: Some("ADDR"), is_arg_optional: false, description: "also serve /metrics and /json over HTTP on ADDR, for example 127.0.0.1:9100", },
+    OptionDesc { short_name: None, long_name: Some("log"), arg_name: Some("TARGET"), is_arg_optional: false, description: "log filesystems crossing thresholds to TARGET (syslog or journal)", },
+    OptionDesc { short_name: None, long_name: Some("max-age"), arg_name: Some("SECONDS"), is_arg_optional: false, description: "get usage again if cached usage is older than SECONDS (2 by default)", },
+    OptionDesc { short_name: None, long_name: Some("socket"), arg_name: Some("PATH"), is_arg_optional: false, description: "listen on the Unix socket PATH", },
+    OptionDesc { short_name: Some('w'), long_name: Some("warning"), arg_name: Some("PERCENT"), is_arg_optional: false, description: "report a warning state for capacities of at least PERCENT", },
+    OptionDesc { short_name: None, long_name: Some("help"), arg_name: None, is_arg_optional: false, description: "display this help and exit", },
+];
+
+/// The maximal length of a request.
+const MAX_REQUEST_LEN: usize = 1 << 20;
+
+/// The maximal length of the header of a HTTP request.
+const MAX_HTTP_HEADER_LEN: usize = 8192;
+
+/// A timeout of writing a response, so that a client which doesn't read responses can't block
+/// other clients for long.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximal number of connected clients.
+const MAX_CLIENTS: usize = 256;
+
+/// The minimal interval of getting usage for checking thresholds.
+const MIN_THRESHOLD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The well-known name of the D-Bus service.
+#[cfg(feature = "dbus")]
+const DBUS_NAME: &str = "io.github.luckboy.Mntdf";
+
+/// The path of the D-Bus object.
+#[cfg(feature = "dbus")]
+const DBUS_PATH: &str = "/io/github/luckboy/Mntdf";
+
+/// The D-Bus interface of the object.
+#[cfg(feature = "dbus")]
+const DBUS_INTERFACE: &str = "io.github.luckboy.Mntdf";
+
+#[cfg(feature = "dbus")]
+const DBUS_INTROSPECTION: &str = "<!DOCTYPE node PUBLIC \"-//freedesktop//DTD D-BUS Object Introspection 1.0//EN\"
+ \"http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd\">
+<node>
+  <interface name=\"org.freedesktop.DBus.Introspectable\">
+    <method name=\"Introspect\">
+      <arg name=\"xml_data\" type=\"s\" direction=\"out\"/>
+    </method>
+  </interface>
+  <interface name=\"io.github.luckboy.Mntdf\">
+    <method name=\"ListFilesystems\">
+      <arg name=\"filesystems\" type=\"a(sssttxu)\" direction=\"out\"/>
+    </method>
+    <method name=\"GetUsage\">
+      <arg name=\"path\" type=\"s\" direction=\"in\"/>
+      <arg name=\"usage\" type=\"(sssttxu)\" direction=\"out\"/>
+    </method>
+    <signal name=\"UsageThresholdCrossed\">
+      <arg name=\"target\" type=\"s\"/>
+      <arg name=\"state\" type=\"s\"/>
+      <arg name=\"pcent\" type=\"u\"/>
+    </signal>
+  </interface>
+</node>
+";
+
+/// Cached usage of filesystems.
+struct Cache
+{
+    usage_entries: Vec<crate::UsageEntry>,
+    time: Option<Instant>,
+    threshold_states: HashMap<String, PluginState>,
+    /// The mount points, the states and the capacities of filesystems which crossed thresholds
+    /// since the last check.
+    crossings: Vec<(String, PluginState, u64)>,
+}
+
+impl Cache
+{
+    fn refresh(&mut self, fields: &[Field], opts: &crate::Options)
+    {
+        let (usage_entries, _) = crate::get_usage_entries(None, fields, false, &mut UsageSamples::new(), opts);
+        if opts.warning_percent.is_some() || opts.critical_percent.is_some() {
+            for (usage_entry, state) in crate::threshold_crossings(usage_entries.as_slice(), &mut self.threshold_states, opts) {
+                if let Some(log_target) = opts.log_target {
+                    if let Err(err) = crate::log_threshold_crossing(usage_entry, state, log_target, opts) {
+                        eprintln!("{}", err);
+                    }
+                }
+                self.crossings.push((usage_entry.mount_point.clone(), state, usage_entry.capacity(opts)));
+            }
+        }
+        self.usage_entries = usage_entries;
+        self.time = Some(Instant::now());
+    }
+
+    /// Returns the cached usage and gets usage again if the cached usage is older than `max_age`.
+    fn usage_entries(&mut self, max_age: Duration, fields: &[Field], opts: &crate::Options) -> &[crate::UsageEntry]
+    {
+        if self.time.map(|time| time.elapsed() > max_age).unwrap_or(true) {
+            self.refresh(fields, opts);
+        }
+        self.usage_entries.as_slice()
+    }
+}
+
+/// Returns `$XDG_RUNTIME_DIR/mntdf.sock` or `mntdf.sock` in the private `/tmp/mntdf-UID`
+/// directory if the runtime directory isn't set. The private directory is created if it doesn't
+/// exist.
+fn default_socket_path() -> Result<PathBuf>
+{
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(Path::new(&dir).join("mntdf.sock"));
+    }
+    let uid = unsafe { libc::getuid() };
+    let dir = PathBuf::from(format!("/tmp/mntdf-{}", uid));
+    match fs::DirBuilder::new().mode(0o700).create(dir.as_path()) {
+        Ok(())                                               => (),
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => (),
+        Err(err)                                             => return Err(err),
+    }
+    // Another user can create the directory first, so the directory is used only if it is a
+    // private directory of the user.
+    let metadata = fs::symlink_metadata(dir.as_path())?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(Error::new(ErrorKind::PermissionDenied, format!("{} isn't a private directory", dir.to_string_lossy())));
+    }
+    Ok(dir.join("mntdf.sock"))
+}
+
+fn write_message(stream: &mut UnixStream, s: &str) -> Result<()>
+{
+    stream.write_all(&(s.len() as u32).to_be_bytes())?;
+    stream.write_all(s.as_bytes())?;
+    stream.flush()
+}
+
+fn error_response(message: &str) -> String
+{
+    format!("{{\"error\":{}}}", json_string(message))
+}
+
+/// Handles a request and returns a response. The following commands are supported:
+///
+/// * `{"command":"usage"}` returns `{"filesystems":[...]}` with usage of all filesystems, and an
+///   optional `targets` array selects filesystems by mount points.
+/// * `{"command":"refresh"}` gets usage again.
+/// * `{"command":"ping"}` only checks the connection.
+fn handle_request(request: &[u8], cache: &mut Cache, max_age: Duration, fields: &[Field], opts: &crate::Options) -> String
+{
+    let request = match str::from_utf8(request).map_err(|err| err.to_string()).and_then(parse_json) {
+        Ok(request) => request,
+        Err(err)    => return error_response(err.as_str()),
+    };
+    match request.get("command") {
+        Some(JsonValue::String(command)) if command == "usage" => {
+            let targets: Option<Vec<&str>> = match request.get("targets") {
+                Some(JsonValue::Array(values)) => {
+                    let mut targets: Vec<&str> = Vec::new();
+                    for value in values.iter() {
+                        match value {
+                            JsonValue::String(target) => targets.push(target.as_str()),
+                            _                         => return error_response("targets: expected strings"),
+                        }
+                    }
+                    Some(targets)
+                },
+                Some(_) => return error_response("targets: expected array"),
+                None    => None,
+            };
+            let objects: Vec<String> = cache.usage_entries(max_age, fields, opts).iter()
+                .filter(|usage_entry| targets.as_ref().map(|targets| targets.contains(&usage_entry.mount_point.as_str())).unwrap_or(true))
+                .map(|usage_entry| crate::usage_entry_to_json(usage_entry, fields, opts))
+                .collect();
+            format!("{{\"filesystems\":[{}]}}", objects.join(","))
+        },
+        Some(JsonValue::String(command)) if command == "refresh" => {
+            cache.refresh(fields, opts);
+            String::from("{\"ok\":true}")
+        },
+        Some(JsonValue::String(command)) if command == "ping" => String::from("{\"ok\":true}"),
+        Some(_) => error_response("unknown command"),
+        None    => error_response("command: expected string"),
+    }
+}
+
+/// A stream of a connected client.
+enum ClientStream
+{
+    Unix(UnixStream),
+    Http(TcpStream),
+}
+
+/// A connected client with received data of incomplete requests.
+struct Client
+{
+    stream: ClientStream,
+    buf: Vec<u8>,
+}
+
+impl AsRawFd for Client
+{
+    fn as_raw_fd(&self) -> RawFd
+    {
+        match &self.stream {
+            ClientStream::Unix(stream) => stream.as_raw_fd(),
+            ClientStream::Http(stream) => stream.as_raw_fd(),
+        }
+    }
+}
+
+fn http_response(status: &str, content_type: &str, body: &str) -> String
+{
+    format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, content_type, body.len(), body)
+}
+
+/// Handles a HTTP request and returns a response. `GET /metrics` returns usage as Prometheus
+/// metrics and `GET /json` returns usage as a JSON array.
+fn handle_http_request(header: &[u8], cache: &mut Cache, max_age: Duration, fields: &[Field], opts: &crate::Options) -> String
+{
+    let header = String::from_utf8_lossy(header);
+    let mut tokens = header.lines().next().unwrap_or("").split(' ');
+    let (method, target) = match (tokens.next(), tokens.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return http_response("400 Bad Request", "text/plain", "Bad request\n"),
+    };
+    let path = target.split('?').next().unwrap_or(target);
+    if path != "/metrics" && path != "/json" {
+        return http_response("404 Not Found", "text/plain", "Not found\n");
+    }
+    if method != "GET" && method != "HEAD" {
+        return http_response("405 Method Not Allowed", "text/plain", "Method not allowed\n");
+    }
+    let usage_entries = cache.usage_entries(max_age, fields, opts);
+    let (content_type, body) = if path == "/metrics" {
+        ("text/plain; version=0.0.4", crate::prometheus_text(usage_entries))
+    } else {
+        let objects: Vec<String> = usage_entries.iter().map(|usage_entry| crate::usage_entry_to_json(usage_entry, fields, opts)).collect();
+        ("application/json", format!("[{}]\n", objects.join(",")))
+    };
+    let mut response = http_response("200 OK", content_type, body.as_str());
+    if method == "HEAD" {
+        response.truncate(response.len() - body.len());
+    }
+    response
+}
+
+/// Reads data which is received from a client and answers its complete requests. A client of the
+/// Unix socket can send many requests, but a HTTP connection is closed after a response. `false` is
+/// returned if the connection should be closed.
+fn handle_client(client: &mut Client, cache: &mut Cache, max_age: Duration, fields: &[Field], opts: &crate::Options) -> bool
+{
+    // The read doesn't block because the client is polled before.
+    let mut data = [0u8; 4096];
+    let res = match &mut client.stream {
+        ClientStream::Unix(stream) => stream.read(&mut data),
+        ClientStream::Http(stream) => stream.read(&mut data),
+    };
+    match res {
+        Ok(0) | Err(_) => return false,
+        Ok(len)        => client.buf.extend_from_slice(&data[..len]),
+    }
+    match &mut client.stream {
+        ClientStream::Unix(stream) => {
+            while client.buf.len() >= 4 {
+                let len = u32::from_be_bytes(client.buf[..4].try_into().unwrap()) as usize;
+                if len > MAX_REQUEST_LEN {
+                    let _ = write_message(stream, error_response("Request is too long").as_str());
+                    return false;
+                }
+                if client.buf.len() < len + 4 {
+                    break;
+                }
+                let request: Vec<u8> = client.buf.drain(..(len + 4)).skip(4).collect();
+                let response = handle_request(request.as_slice(), cache, max_age, fields, opts);
+                if write_message(stream, response.as_str()).is_err() {
+                    return false;
+                }
+            }
+            true
+        },
+        ClientStream::Http(stream) => {
+            let response = if client.buf.windows(4).any(|window| window == b"\r\n\r\n") {
+                handle_http_request(client.buf.as_slice(), cache, max_age, fields, opts)
+            } else if client.buf.len() > MAX_HTTP_HEADER_LEN {
+                http_response("431 Request Header Fields Too Large", "text/plain", "Request header is too large\n")
+            } else {
+                return true;
+            };
+            let _ = stream.write_all(response.as_bytes());
+            false
+        },
+    }
+}
+
+/// Adds an accepted client unless there are too many clients.
+fn add_client(clients: &mut Vec<Client>, stream: ClientStream)
+{
+    if clients.len() >= MAX_CLIENTS {
+        return;
+    }
+    let res = match &stream {
+        ClientStream::Unix(stream) => stream.set_write_timeout(Some(WRITE_TIMEOUT)),
+        ClientStream::Http(stream) => stream.set_write_timeout(Some(WRITE_TIMEOUT)),
+    };
+    if res.is_ok() {
+        clients.push(Client { stream, buf: Vec::new(), });
+    }
+}
+
+#[cfg(feature = "dbus")]
+fn write_dbus_usage(writer: &mut MessageWriter, usage_entry: &crate::UsageEntry, opts: &crate::Options)
+{
+    writer.begin_struct();
+    writer.write_string(usage_entry.file_system.as_str());
+    writer.write_string(usage_entry.file_system_type.as_str());
+    writer.write_string(usage_entry.mount_point.as_str());
+    writer.write_u64(usage_entry.total_bytes);
+    writer.write_u64(usage_entry.used_bytes);
+    writer.write_i64(usage_entry.available_bytes);
+    writer.write_u32(usage_entry.capacity(opts) as u32);
+}
+
+/// Handles a D-Bus message. The following methods of the object are supported:
+///
+/// * `ListFilesystems()` returns usage of all filesystems as an array of the source, the
+///   filesystem type, the mount point, the size, the used bytes, the available bytes and the
+///   capacity.
+/// * `GetUsage(path)` returns usage of the filesystem which contains the path.
+/// * `Introspect()` of the `org.freedesktop.DBus.Introspectable` interface.
+#[cfg(feature = "dbus")]
+fn handle_dbus_message(conn: &mut Connection, message: &Message, cache: &mut Cache, max_age: Duration, fields: &[Field], opts: &crate::Options) -> Result<()>
+{
+    if message.message_type != METHOD_CALL {
+        return Ok(());
+    }
+    let member = message.member.as_deref().unwrap_or("");
+    let interface = message.interface.as_deref();
+    let reply = if message.path.as_deref() != Some(DBUS_PATH) {
+        Message::error(message, "org.freedesktop.DBus.Error.UnknownObject", format!("No such object {}", message.path.as_deref().unwrap_or("")).as_str())
+    } else if member == "Introspect" && interface.map(|interface| interface == "org.freedesktop.DBus.Introspectable").unwrap_or(true) {
+        let mut body_writer = MessageWriter::new();
+        body_writer.write_string(DBUS_INTROSPECTION);
+        Message::method_return(message).with_body("s", body_writer)
+    } else if member == "ListFilesystems" && interface.map(|interface| interface == DBUS_INTERFACE).unwrap_or(true) {
+        let mut body_writer = MessageWriter::new();
+        let positions = body_writer.begin_array(8);
+        for usage_entry in cache.usage_entries(max_age, fields, opts).iter() {
+            write_dbus_usage(&mut body_writer, usage_entry, opts);
+        }
+        body_writer.end_array(positions);
+        Message::method_return(message).with_body("a(sssttxu)", body_writer)
+    } else if member == "GetUsage" && interface.map(|interface| interface == DBUS_INTERFACE).unwrap_or(true) {
+        match (message.signature.as_str(), message.body_reader().read_string()) {
+            ("s", Ok(path)) => {
+                // The filesystem of the longest mount point which contains the path is chosen.
+                let usage_entry = cache.usage_entries(max_age, fields, opts).iter()
+                    .filter(|usage_entry| Path::new(path.as_str()).starts_with(usage_entry.mount_point.as_str()))
+                    .max_by_key(|usage_entry| usage_entry.mount_point.len());
+                match usage_entry {
+                    Some(usage_entry) => {
+                        let mut body_writer = MessageWriter::new();
+                        write_dbus_usage(&mut body_writer, usage_entry, opts);
+                        Message::method_return(message).with_body("(sssttxu)", body_writer)
+                    },
+                    None => Message::error(message, "io.github.luckboy.Mntdf.Error.NotFound", format!("No filesystem for {}", path).as_str()),
+                }
+            },
+            _ => Message::error(message, "org.freedesktop.DBus.Error.InvalidArgs", "Expected a path as a string"),
+        }
+    } else {
+        Message::error(message, "org.freedesktop.DBus.Error.UnknownMethod", format!("No such method {}", member).as_str())
+    };
+    if message.flags & NO_REPLY_EXPECTED != 0 {
+        return Ok(());
+    }
+    conn.send(reply)?;
+    Ok(())
+}
+
+/// Receives D-Bus messages and handles them.
+#[cfg(feature = "dbus")]
+fn handle_dbus_messages(conn: &mut Connection, cache: &mut Cache, max_age: Duration, fields: &[Field], opts: &crate::Options) -> Result<()>
+{
+    for message in conn.receive()?.iter() {
+        handle_dbus_message(conn, message, cache, max_age, fields, opts)?;
+    }
+    Ok(())
+}
+
+/// Emits the `UsageThresholdCrossed` signal with the mount point, the state and the capacity of a
+/// filesystem.
+#[cfg(feature = "dbus")]
+fn emit_dbus_crossing(conn: &mut Connection, mount_point: &str, state: PluginState, percent: u64) -> Result<()>
+{
+    let mut body_writer = MessageWriter::new();
+    body_writer.write_string(mount_point);
+    body_writer.write_string(state.name());
+    body_writer.write_u32(percent as u32);
+    conn.send(Message::signal(DBUS_PATH, DBUS_INTERFACE, "UsageThresholdCrossed").with_body("ssu", body_writer))?;
+    Ok(())
+}
+
+fn print_daemon_help(program_name: &str)
+{
+    println!("Usage: {} daemon [OPTION]...", program_name);
+    println!("Cache usage of filesystems and answer queries on a Unix socket. Each request and each");
+    println!("response is a JSON object prefixed by its length as a 32-bit big-endian number.");
+    println!("Requests are {{\"command\":\"usage\"}} with an optional \"targets\" array of mount");
+    println!("points, {{\"command\":\"refresh\"}} and {{\"command\":\"ping\"}}. With --listen, usage");
+    println!("is also served over HTTP as Prometheus metrics on /metrics and as JSON on /json.");
+    println!("With --log, filesystems crossing the thresholds of -w and -c are logged.");
+    #[cfg(feature = "dbus")]
+    {
+        println!("With --dbus, the {} service provides the ListFilesystems and GetUsage", DBUS_NAME);
+        println!("methods and the UsageThresholdCrossed signal on the {} object.", DBUS_PATH);
+    }
+    println!();
+    println!("Options:");
+    print_option_help(DAEMON_OPTIONS);
+}
+
+/// Runs the `daemon` subcommand and returns an exit status. The first argument should be the
+/// subcommand name.
+pub fn daemon_main(program_name: &str, args: &[String]) -> i32
+{
+    let mut opt_parser = Parser::new(args, DAEMON_OPTIONS);
+    let mut opts = crate::Options::new();
+    let mut socket_path: Option<PathBuf> = None;
+    let mut max_age = Duration::from_secs(2);
+    let mut listen_addr: Option<String> = None;
+    #[cfg(feature = "dbus")]
+    let mut bus: Option<Bus> = None;
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt(Short('a'), _))) => opts.all_flag = true,
+            Some(Ok(Opt(Short('l'), _))) => opts.local_flag = true,
+            Some(Ok(Opt(Short('w'), Some(opt_arg)))) => {
+                match crate::parse_percent(opt_arg.as_str()) {
+                    Some(percent) => opts.warning_percent = Some(percent),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid percentage"), opt_arg);
+                        return 1;
+                    },
+                }
+            },
+            Some(Ok(Opt(Short('c'), Some(opt_arg)))) => {
+                match crate::parse_percent(opt_arg.as_str()) {
+                    Some(percent) => opts.critical_percent = Some(percent),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid percentage"), opt_arg);
+                        return 1;
+                    },
+                }
+            },
+            #[cfg(feature = "dbus")]
+            Some(Ok(Opt(Long("dbus"), Some(opt_arg)))) => {
+                match Bus::from_name(opt_arg.as_str()) {
+                    Some(tmp_bus) => bus = Some(tmp_bus),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid bus"), opt_arg);
+                        return 1;
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("log"), Some(opt_arg)))) => {
+                match LogTarget::from_name(opt_arg.as_str()) {
+                    Some(log_target) => opts.log_target = Some(log_target),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid log target"), opt_arg);
+                        return 1;
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("listen"), Some(opt_arg)))) => listen_addr = Some(opt_arg),
+            Some(Ok(Opt(Long("max-age"), Some(opt_arg)))) => {
+                match opt_arg.parse::<f64>().ok().and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()) {
+                    Some(duration) => max_age = duration,
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid interval"), opt_arg);
+                        return 1;
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("socket"), Some(opt_arg)))) => socket_path = Some(PathBuf::from(opt_arg)),
+            Some(Ok(Opt(Long("help"), _))) => {
+                print_daemon_help(program_name);
+                return 0;
+            },
+            Some(Ok(Opt(name, _))) => {
+                eprintln!("{} -- {:?}", gettext("unknown option"), name);
+                return 1;
+            },
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    if !opt_parser.operands().is_empty() {
+        eprintln!("{}", gettext("too many arguments"));
+        return 1;
+    }
+    if opts.log_target.is_some() && opts.warning_percent.is_none() && opts.critical_percent.is_none() {
+        eprintln!("{}", gettext_fmt("option {} requires {} or {}", &["--log", "-w", "-c"]));
+        return 1;
+    }
+    opts.output_format = crate::OutputFormat::Json;
+    let fields = crate::selected_fields(&opts);
+    let socket_path = match socket_path {
+        Some(socket_path) => socket_path,
+        None => {
+            match default_socket_path() {
+                Ok(socket_path) => socket_path,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return 1;
+                },
+            }
+        },
+    };
+    // A socket of another user isn't trusted, so it is neither used nor removed.
+    if let Ok(metadata) = fs::symlink_metadata(socket_path.as_path()) {
+        if metadata.uid() != unsafe { libc::getuid() } {
+            eprintln!("{}: {}", socket_path.to_string_lossy(), gettext("Socket is owned by another user"));
+            return 1;
+        }
+    }
+    // A socket of a daemon which isn't running is removed, but a socket of a running daemon is
+    // left.
+    if UnixStream::connect(socket_path.as_path()).is_ok() {
+        eprintln!("{}: {}", socket_path.to_string_lossy(), gettext("Daemon is already running"));
+        return 1;
+    }
+    let _ = fs::remove_file(socket_path.as_path());
+    let listener = match UnixListener::bind(socket_path.as_path()) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("{}: {}", socket_path.to_string_lossy(), err);
+            return 1;
+        },
+    };
+    if let Err(err) = fs::set_permissions(socket_path.as_path(), fs::Permissions::from_mode(0o600)) {
+        eprintln!("{}: {}", socket_path.to_string_lossy(), err);
+        return 1;
+    }
+    let tcp_listener = match listen_addr {
+        Some(listen_addr) => {
+            match TcpListener::bind(listen_addr.as_str()) {
+                Ok(tcp_listener) => Some(tcp_listener),
+                Err(err) => {
+                    eprintln!("{}: {}", listen_addr, err);
+                    return 1;
+                },
+            }
+        },
+        None => None,
+    };
+    #[cfg(feature = "dbus")]
+    let mut dbus_conn = match bus {
+        Some(bus) => {
+            match Connection::open(bus).and_then(|mut conn| conn.request_name(DBUS_NAME).map(|_| conn)) {
+                Ok(conn) => Some(conn),
+                Err(err) => {
+                    eprintln!("D-Bus: {}", err);
+                    return 1;
+                },
+            }
+        },
+        None => None,
+    };
+    let mut cache = Cache { usage_entries: Vec::new(), time: None, threshold_states: HashMap::new(), crossings: Vec::new(), };
+    cache.refresh(fields.as_slice(), &opts);
+    // Usage is also got periodically if filesystems crossing thresholds are logged or signaled.
+    let is_threshold_checked = opts.warning_percent.is_some() || opts.critical_percent.is_some();
+    #[cfg(feature = "dbus")]
+    let is_threshold_checked = is_threshold_checked && (opts.log_target.is_some() || dbus_conn.is_some());
+    #[cfg(not(feature = "dbus"))]
+    let is_threshold_checked = is_threshold_checked && opts.log_target.is_some();
+    let timeout = if is_threshold_checked { max_age.max(MIN_THRESHOLD_INTERVAL).as_millis() as libc::c_int } else { -1 };
+    let mut clients: Vec<Client> = Vec::new();
+    loop {
+        #[cfg(feature = "dbus")]
+        if let Some(dbus_conn) = &mut dbus_conn {
+            for (mount_point, state, percent) in cache.crossings.iter() {
+                if let Err(err) = emit_dbus_crossing(dbus_conn, mount_point.as_str(), *state, *percent) {
+                    eprintln!("D-Bus: {}", err);
+                    return 1;
+                }
+            }
+            // Messages which are received with a reply of a call are already read, so they are
+            // handled without polling.
+            if dbus_conn.has_messages() {
+                if let Err(err) = handle_dbus_messages(dbus_conn, &mut cache, max_age, fields.as_slice(), &opts) {
+                    eprintln!("D-Bus: {}", err);
+                    return 1;
+                }
+            }
+        }
+        cache.crossings.clear();
+        // All listeners and clients are polled in one thread, so that requests are handled
+        // sequentially without blocking on any client.
+        let mut poll_fds = vec![libc::pollfd { fd: listener.as_raw_fd(), events: libc::POLLIN, revents: 0, }];
+        if let Some(tcp_listener) = &tcp_listener {
+            poll_fds.push(libc::pollfd { fd: tcp_listener.as_raw_fd(), events: libc::POLLIN, revents: 0, });
+        }
+        #[cfg(feature = "dbus")]
+        if let Some(dbus_conn) = &dbus_conn {
+            poll_fds.push(libc::pollfd { fd: dbus_conn.as_raw_fd(), events: libc::POLLIN, revents: 0, });
+        }
+        let first_client_index = poll_fds.len();
+        poll_fds.extend(clients.iter().map(|client| libc::pollfd { fd: client.as_raw_fd(), events: libc::POLLIN, revents: 0, }));
+        let res = unsafe { libc::poll(poll_fds.as_mut_ptr(), poll_fds.len() as libc::nfds_t, timeout) };
+        if res == 0 {
+            cache.refresh(fields.as_slice(), &opts);
+            continue;
+        }
+        if res == -1 {
+            let err = Error::last_os_error();
+            if err.kind() != ErrorKind::Interrupted {
+                eprintln!("{}", err);
+                return 1;
+            }
+            continue;
+        }
+        let mut client_poll_fds = poll_fds[first_client_index..].iter();
+        clients.retain_mut(|client| {
+                client_poll_fds.next().map(|poll_fd| poll_fd.revents == 0).unwrap_or(true) || handle_client(client, &mut cache, max_age, fields.as_slice(), &opts)
+        });
+        if poll_fds[0].revents != 0 {
+            match listener.accept() {
+                Ok((stream, _)) => add_client(&mut clients, ClientStream::Unix(stream)),
+                Err(err)        => eprintln!("{}", err),
+            }
+        }
+        if let Some(tcp_listener) = &tcp_listener {
+            if poll_fds[1].revents != 0 {
+                match tcp_listener.accept() {
+                    Ok((stream, _)) => add_client(&mut clients, ClientStream::Http(stream)),
+                    Err(err)        => eprintln!("{}", err),
+                }
+            }
+        }
+        #[cfg(feature = "dbus")]
+        if let Some(dbus_conn) = &mut dbus_conn {
+            if poll_fds[first_client_index - 1].revents != 0 {
+                if let Err(err) = handle_dbus_messages(dbus_conn, &mut cache, max_age, fields.as_slice(), &opts) {
+                    eprintln!("D-Bus: {}", err);
+                    return 1;
+                }
+            }
+        }
+    }
+}
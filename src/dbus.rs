@@ -0,0 +1,760 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::convert::TryInto;
+use std::env;
+use std::io::*;
+use std::os::unix::net::UnixStream;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::RawFd;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::SocketAddr;
+
+/// The default address of the system bus.
+const SYSTEM_BUS_ADDRESS: &str = "unix:path=/var/run/dbus/system_bus_socket";
+
+/// The maximal length of a message according to the D-Bus specification.
+const MAX_MESSAGE_LEN: usize = 1 << 27;
+
+pub const METHOD_CALL: u8 = 1;
+pub const METHOD_RETURN: u8 = 2;
+pub const ERROR: u8 = 3;
+pub const SIGNAL: u8 = 4;
+
+/// The flag of a method call which doesn't expect a reply.
+pub const NO_REPLY_EXPECTED: u8 = 0x1;
+
+const PATH_FIELD: u8 = 1;
+const INTERFACE_FIELD: u8 = 2;
+const MEMBER_FIELD: u8 = 3;
+const ERROR_NAME_FIELD: u8 = 4;
+const REPLY_SERIAL_FIELD: u8 = 5;
+const DESTINATION_FIELD: u8 = 6;
+const SENDER_FIELD: u8 = 7;
+const SIGNATURE_FIELD: u8 = 8;
+
+/// A bus of D-Bus.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Bus
+{
+    Session,
+    System,
+}
+
+impl Bus
+{
+    /// Returns the bus that has the specified name.
+    pub fn from_name(name: &str) -> Option<Bus>
+    {
+        match name {
+            "session" => Some(Bus::Session),
+            "system"  => Some(Bus::System),
+            _         => None,
+        }
+    }
+
+    fn address(self) -> Option<String>
+    {
+        match self {
+            Bus::Session => env::var("DBUS_SESSION_BUS_ADDRESS").ok().filter(|address| !address.is_empty()),
+            Bus::System  => Some(env::var("DBUS_SYSTEM_BUS_ADDRESS").ok().filter(|address| !address.is_empty()).unwrap_or_else(|| String::from(SYSTEM_BUS_ADDRESS))),
+        }
+    }
+}
+
+/// A writer of values in the marshaling format of D-Bus in little endian.
+///
+/// Values are aligned relative to the start of the buffer, so a body can be written by a separate
+/// writer because a body starts at a boundary of eight bytes.
+pub struct MessageWriter
+{
+    buf: Vec<u8>,
+}
+
+impl MessageWriter
+{
+    pub fn new() -> MessageWriter
+    {
+        MessageWriter { buf: Vec::new(), }
+    }
+
+    fn align(&mut self, n: usize)
+    {
+        self.buf.resize(self.buf.len().div_ceil(n) * n, 0);
+    }
+
+    pub fn write_byte(&mut self, x: u8)
+    {
+        self.buf.push(x);
+    }
+
+    pub fn write_u32(&mut self, x: u32)
+    {
+        self.align(4);
+        self.buf.extend_from_slice(&x.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, x: u64)
+    {
+        self.align(8);
+        self.buf.extend_from_slice(&x.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, x: i64)
+    {
+        self.align(8);
+        self.buf.extend_from_slice(&x.to_le_bytes());
+    }
+
+    /// Writes a string or an object path.
+    pub fn write_string(&mut self, s: &str)
+    {
+        self.write_u32(s.len() as u32);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+    }
+
+    pub fn write_signature(&mut self, s: &str)
+    {
+        self.buf.push(s.len() as u8);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+    }
+
+    /// Starts an array of elements with the specified alignment and returns the position of the
+    /// array length, which should be passed to `end_array`.
+    pub fn begin_array(&mut self, element_alignment: usize) -> (usize, usize)
+    {
+        self.write_u32(0);
+        let len_pos = self.buf.len() - 4;
+        self.align(element_alignment);
+        (len_pos, self.buf.len())
+    }
+
+    pub fn end_array(&mut self, positions: (usize, usize))
+    {
+        let (len_pos, start_pos) = positions;
+        let len = (self.buf.len() - start_pos) as u32;
+        self.buf[len_pos..(len_pos + 4)].copy_from_slice(&len.to_le_bytes());
+    }
+
+    /// Starts a structure which is aligned to eight bytes.
+    pub fn begin_struct(&mut self)
+    {
+        self.align(8);
+    }
+}
+
+/// A reader of values in the marshaling format of D-Bus.
+pub struct MessageReader<'a>
+{
+    buf: &'a [u8],
+    pos: usize,
+    is_big_endian: bool,
+}
+
+impl<'a> MessageReader<'a>
+{
+    fn new(buf: &'a [u8], is_big_endian: bool) -> MessageReader<'a>
+    {
+        MessageReader { buf, pos: 0, is_big_endian, }
+    }
+
+    fn invalid_message_error() -> Error
+    {
+        Error::new(ErrorKind::InvalidData, "Invalid D-Bus message")
+    }
+
+    fn align(&mut self, n: usize)
+    {
+        self.pos = self.pos.div_ceil(n) * n;
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]>
+    {
+        match self.buf.get(self.pos..(self.pos + len)) {
+            Some(bytes) => {
+                self.pos += len;
+                Ok(bytes)
+            },
+            None => Err(Self::invalid_message_error()),
+        }
+    }
+
+    pub fn read_byte(&mut self) -> Result<u8>
+    {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32>
+    {
+        self.align(4);
+        let bytes: [u8; 4] = self.read_bytes(4)?.try_into().unwrap();
+        Ok(if self.is_big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    }
+
+    /// Reads a string or an object path.
+    pub fn read_string(&mut self) -> Result<String>
+    {
+        let len = self.read_u32()? as usize;
+        let bytes = self.read_bytes(len + 1)?;
+        String::from_utf8(bytes[..len].to_vec()).map_err(|_| Self::invalid_message_error())
+    }
+
+    pub fn read_signature(&mut self) -> Result<String>
+    {
+        let len = self.read_byte()? as usize;
+        let bytes = self.read_bytes(len + 1)?;
+        String::from_utf8(bytes[..len].to_vec()).map_err(|_| Self::invalid_message_error())
+    }
+}
+
+/// A message of D-Bus.
+#[derive(Clone, Debug, Default)]
+pub struct Message
+{
+    pub message_type: u8,
+    pub flags: u8,
+    pub serial: u32,
+    pub path: Option<String>,
+    pub interface: Option<String>,
+    pub member: Option<String>,
+    pub error_name: Option<String>,
+    pub reply_serial: Option<u32>,
+    pub destination: Option<String>,
+    pub sender: Option<String>,
+    pub signature: String,
+    pub body: Vec<u8>,
+    pub is_big_endian: bool,
+}
+
+impl Message
+{
+    /// Creates a method call.
+    pub fn method_call(destination: &str, path: &str, interface: &str, member: &str) -> Message
+    {
+        Message {
+            message_type: METHOD_CALL,
+            path: Some(String::from(path)),
+            interface: Some(String::from(interface)),
+            member: Some(String::from(member)),
+            destination: Some(String::from(destination)),
+            ..Message::default()
+        }
+    }
+
+    /// Creates a signal.
+    pub fn signal(path: &str, interface: &str, member: &str) -> Message
+    {
+        Message {
+            message_type: SIGNAL,
+            path: Some(String::from(path)),
+            interface: Some(String::from(interface)),
+            member: Some(String::from(member)),
+            ..Message::default()
+        }
+    }
+
+    /// Creates a reply to a method call.
+    pub fn method_return(call: &Message) -> Message
+    {
+        Message {
+            message_type: METHOD_RETURN,
+            reply_serial: Some(call.serial),
+            destination: call.sender.clone(),
+            ..Message::default()
+        }
+    }
+
+    /// Creates an error reply to a method call with a message.
+    pub fn error(call: &Message, error_name: &str, message: &str) -> Message
+    {
+        let mut body_writer = MessageWriter::new();
+        body_writer.write_string(message);
+        Message {
+            message_type: ERROR,
+            error_name: Some(String::from(error_name)),
+            reply_serial: Some(call.serial),
+            destination: call.sender.clone(),
+            ..Message::default()
+        }.with_body("s", body_writer)
+    }
+
+    /// Sets the body written by a writer with its signature.
+    pub fn with_body(mut self, signature: &str, body_writer: MessageWriter) -> Message
+    {
+        self.signature = String::from(signature);
+        self.body = body_writer.buf;
+        self
+    }
+
+    /// Returns a reader of the body.
+    pub fn body_reader(&self) -> MessageReader<'_>
+    {
+        MessageReader::new(self.body.as_slice(), self.is_big_endian)
+    }
+
+    fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut writer = MessageWriter::new();
+        writer.write_byte(b'l');
+        writer.write_byte(self.message_type);
+        writer.write_byte(self.flags);
+        writer.write_byte(1);
+        writer.write_u32(self.body.len() as u32);
+        writer.write_u32(self.serial);
+        let positions = writer.begin_array(8);
+        let string_fields = [
+            (PATH_FIELD, "o", &self.path),
+            (INTERFACE_FIELD, "s", &self.interface),
+            (MEMBER_FIELD, "s", &self.member),
+            (ERROR_NAME_FIELD, "s", &self.error_name),
+            (DESTINATION_FIELD, "s", &self.destination),
+        ];
+        for (code, signature, value) in string_fields.iter() {
+            if let Some(value) = value {
+                writer.begin_struct();
+                writer.write_byte(*code);
+                writer.write_signature(signature);
+                writer.write_string(value.as_str());
+            }
+        }
+        if let Some(reply_serial) = self.reply_serial {
+            writer.begin_struct();
+            writer.write_byte(REPLY_SERIAL_FIELD);
+            writer.write_signature("u");
+            writer.write_u32(reply_serial);
+        }
+        if !self.signature.is_empty() {
+            writer.begin_struct();
+            writer.write_byte(SIGNATURE_FIELD);
+            writer.write_signature("g");
+            writer.write_signature(self.signature.as_str());
+        }
+        writer.end_array(positions);
+        writer.align(8);
+        writer.buf.extend_from_slice(self.body.as_slice());
+        writer.buf
+    }
+
+    /// Parses a message. Header fields with unknown types are rejected.
+    fn from_bytes(bytes: &[u8]) -> Result<Message>
+    {
+        let is_big_endian = match bytes.first() {
+            Some(b'l') => false,
+            Some(b'B') => true,
+            _          => return Err(MessageReader::invalid_message_error()),
+        };
+        let mut reader = MessageReader::new(bytes, is_big_endian);
+        reader.read_byte()?;
+        let mut message = Message { is_big_endian, ..Message::default() };
+        message.message_type = reader.read_byte()?;
+        message.flags = reader.read_byte()?;
+        reader.read_byte()?;
+        let body_len = reader.read_u32()? as usize;
+        message.serial = reader.read_u32()?;
+        let fields_len = reader.read_u32()? as usize;
+        reader.align(8);
+        let fields_end = reader.pos + fields_len;
+        while reader.pos < fields_end {
+            reader.align(8);
+            let code = reader.read_byte()?;
+            let signature = reader.read_signature()?;
+            match (code, signature.as_str()) {
+                (PATH_FIELD, "o")         => message.path = Some(reader.read_string()?),
+                (INTERFACE_FIELD, "s")    => message.interface = Some(reader.read_string()?),
+                (MEMBER_FIELD, "s")       => message.member = Some(reader.read_string()?),
+                (ERROR_NAME_FIELD, "s")   => message.error_name = Some(reader.read_string()?),
+                (REPLY_SERIAL_FIELD, "u") => message.reply_serial = Some(reader.read_u32()?),
+                (DESTINATION_FIELD, "s")  => message.destination = Some(reader.read_string()?),
+                (SENDER_FIELD, "s")       => message.sender = Some(reader.read_string()?),
+                (SIGNATURE_FIELD, "g")    => message.signature = reader.read_signature()?,
+                (_, "s") | (_, "o")       => {
+                    reader.read_string()?;
+                },
+                (_, "g")                  => {
+                    reader.read_signature()?;
+                },
+                (_, "u")                  => {
+                    reader.read_u32()?;
+                },
+                _                         => return Err(MessageReader::invalid_message_error()),
+            }
+        }
+        reader.align(8);
+        message.body = reader.read_bytes(body_len)?.to_vec();
+        Ok(message)
+    }
+}
+
+/// Returns the length of the first message in the buffer or `None` if the buffer doesn't contain
+/// the whole header.
+fn message_len(buf: &[u8]) -> Option<usize>
+{
+    if buf.len() < 16 {
+        return None;
+    }
+    let read_u32 = |pos: usize| {
+        let bytes: [u8; 4] = buf[pos..(pos + 4)].try_into().unwrap();
+        if buf[0] == b'B' { u32::from_be_bytes(bytes) as usize } else { u32::from_le_bytes(bytes) as usize }
+    };
+    let header_len = (16 + read_u32(12)).div_ceil(8) * 8;
+    Some(header_len + read_u32(4))
+}
+
+/// A connection to a bus.
+pub struct Connection
+{
+    stream: UnixStream,
+    buf: Vec<u8>,
+    serial: u32,
+    /// The received messages which aren't returned yet.
+    messages: Vec<Message>,
+}
+
+impl Connection
+{
+    fn new(stream: UnixStream) -> Connection
+    {
+        Connection { stream, buf: Vec::new(), serial: 0, messages: Vec::new(), }
+    }
+
+    /// Connects to a bus, authenticates by the EXTERNAL mechanism and registers on the bus.
+    pub fn open(bus: Bus) -> Result<Connection>
+    {
+        let address = match bus.address() {
+            Some(address) => address,
+            None          => return Err(Error::new(ErrorKind::NotFound, "DBUS_SESSION_BUS_ADDRESS isn't set")),
+        };
+        // Only the first address of the unix transport is used.
+        let mut stream: Option<UnixStream> = None;
+        for transport in address.split(';') {
+            if let Some(params) = transport.strip_prefix("unix:") {
+                for param in params.split(',') {
+                    match param.split_once('=') {
+                        Some(("path", path))     => stream = Some(UnixStream::connect(unescape_address_value(path))?),
+                        Some(("abstract", name)) => stream = Some(UnixStream::connect_addr(&SocketAddr::from_abstract_name(unescape_address_value(name).as_bytes())?)?),
+                        _                        => (),
+                    }
+                }
+            }
+            if stream.is_some() {
+                break;
+            }
+        }
+        let mut stream = match stream {
+            Some(stream) => stream,
+            None         => return Err(Error::new(ErrorKind::InvalidInput, format!("Unsupported D-Bus address {}", address))),
+        };
+        let uid: String = format!("{}", unsafe { libc::getuid() }).bytes().map(|b| format!("{:02x}", b)).collect();
+        stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", uid).as_bytes())?;
+        let mut line: Vec<u8> = Vec::new();
+        let mut byte = [0u8; 1];
+        while !line.ends_with(b"\r\n") {
+            if stream.read(&mut byte)? == 0 {
+                return Err(Error::new(ErrorKind::UnexpectedEof, "Unexpected end of D-Bus authentication"));
+            }
+            line.push(byte[0]);
+        }
+        if !line.starts_with(b"OK ") {
+            return Err(Error::new(ErrorKind::PermissionDenied, "D-Bus authentication failed"));
+        }
+        stream.write_all(b"BEGIN\r\n")?;
+        let mut conn = Connection::new(stream);
+        conn.call(Message::method_call("org.freedesktop.DBus", "/org/freedesktop/DBus", "org.freedesktop.DBus", "Hello"))?;
+        Ok(conn)
+    }
+
+    /// Sends a message with a new serial number and returns the serial number.
+    pub fn send(&mut self, mut message: Message) -> Result<u32>
+    {
+        self.serial += 1;
+        message.serial = self.serial;
+        self.stream.write_all(message.to_bytes().as_slice())?;
+        Ok(self.serial)
+    }
+
+    /// Calls a method and waits for the reply. Other messages which are received before the reply
+    /// are kept for `receive`.
+    pub fn call(&mut self, message: Message) -> Result<Message>
+    {
+        let serial = self.send(message)?;
+        loop {
+            if let Some(i) = self.messages.iter().position(|reply| reply.reply_serial == Some(serial)) {
+                let reply = self.messages.remove(i);
+                if reply.message_type == ERROR {
+                    let text = reply.body_reader().read_string().unwrap_or_default();
+                    return Err(Error::other(format!("{}: {}", reply.error_name.unwrap_or_default(), text)));
+                }
+                return Ok(reply);
+            }
+            self.read_messages()?;
+        }
+    }
+
+    /// Returns `true` if there are received messages which aren't returned yet, so that they can
+    /// be got by `receive` without waiting for data.
+    pub fn has_messages(&self) -> bool
+    {
+        !self.messages.is_empty()
+    }
+
+    /// Returns the received messages. If there are no such messages, this method reads data from
+    /// the connection and blocks until some data is available.
+    pub fn receive(&mut self) -> Result<Vec<Message>>
+    {
+        if self.messages.is_empty() {
+            self.read_messages()?;
+        }
+        Ok(self.messages.drain(..).collect())
+    }
+
+    /// Reads data from the connection and adds the complete messages to the received messages.
+    fn read_messages(&mut self) -> Result<()>
+    {
+        let mut data = [0u8; 4096];
+        let len = self.stream.read(&mut data)?;
+        if len == 0 {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "D-Bus connection closed"));
+        }
+        self.buf.extend_from_slice(&data[..len]);
+        while let Some(message_len) = message_len(self.buf.as_slice()) {
+            if message_len > MAX_MESSAGE_LEN {
+                return Err(MessageReader::invalid_message_error());
+            }
+            if self.buf.len() < message_len {
+                break;
+            }
+            let bytes: Vec<u8> = self.buf.drain(..message_len).collect();
+            self.messages.push(Message::from_bytes(bytes.as_slice())?);
+        }
+        Ok(())
+    }
+
+    /// Requests a well-known name for the connection. An error is returned if the name is owned by
+    /// another connection.
+    pub fn request_name(&mut self, name: &str) -> Result<()>
+    {
+        let mut body_writer = MessageWriter::new();
+        body_writer.write_string(name);
+        // The DBUS_NAME_FLAG_DO_NOT_QUEUE flag.
+        body_writer.write_u32(4);
+        let message = Message::method_call("org.freedesktop.DBus", "/org/freedesktop/DBus", "org.freedesktop.DBus", "RequestName").with_body("su", body_writer);
+        let reply = self.call(message)?;
+        // The DBUS_REQUEST_NAME_REPLY_PRIMARY_OWNER result.
+        if reply.body_reader().read_u32()? != 1 {
+            return Err(Error::new(ErrorKind::AddrInUse, format!("Name {} is already owned", name)));
+        }
+        Ok(())
+    }
+}
+
+impl AsRawFd for Connection
+{
+    fn as_raw_fd(&self) -> RawFd
+    {
+        self.stream.as_raw_fd()
+    }
+}
+
+/// Unescapes the `%XX` escape sequences in a value of a D-Bus address.
+fn unescape_address_value(s: &str) -> String
+{
+    let bytes = s.as_bytes();
+    let mut unescaped_bytes: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], s.get((i + 1)..(i + 3)).and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(b)) => {
+                unescaped_bytes.push(b);
+                i += 3;
+            },
+            (b, _) => {
+                unescaped_bytes.push(b);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(unescaped_bytes.as_slice()).into_owned()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn read_u32_at(buf: &[u8], pos: usize) -> u32
+    {
+        u32::from_le_bytes(buf[pos..(pos + 4)].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_method_call_round_trip()
+    {
+        let mut body_writer = MessageWriter::new();
+        body_writer.write_string("io.github.luckboy.Mntdf");
+        body_writer.write_u32(4);
+        let mut message = Message::method_call("org.freedesktop.DBus", "/org/freedesktop/DBus", "org.freedesktop.DBus", "RequestName").with_body("su", body_writer);
+        message.serial = 7;
+        message.flags = NO_REPLY_EXPECTED;
+        let bytes = message.to_bytes();
+        assert_eq!(Some(bytes.len()), message_len(bytes.as_slice()));
+        let parsed_message = Message::from_bytes(bytes.as_slice()).unwrap();
+        assert_eq!(METHOD_CALL, parsed_message.message_type);
+        assert_eq!(NO_REPLY_EXPECTED, parsed_message.flags);
+        assert_eq!(7, parsed_message.serial);
+        assert_eq!(Some("/org/freedesktop/DBus"), parsed_message.path.as_deref());
+        assert_eq!(Some("org.freedesktop.DBus"), parsed_message.interface.as_deref());
+        assert_eq!(Some("RequestName"), parsed_message.member.as_deref());
+        assert_eq!(Some("org.freedesktop.DBus"), parsed_message.destination.as_deref());
+        assert_eq!(None, parsed_message.reply_serial);
+        assert_eq!("su", parsed_message.signature);
+        let mut reader = parsed_message.body_reader();
+        assert_eq!("io.github.luckboy.Mntdf", reader.read_string().unwrap());
+        assert_eq!(4, reader.read_u32().unwrap());
+    }
+
+    #[test]
+    fn test_error_round_trip()
+    {
+        let call = Message {
+            message_type: METHOD_CALL,
+            serial: 3,
+            sender: Some(String::from(":1.2")),
+            ..Message::default()
+        };
+        let mut error = Message::error(&call, "org.freedesktop.DBus.Error.UnknownMethod", "No such method Foo");
+        error.serial = 1;
+        let parsed_error = Message::from_bytes(error.to_bytes().as_slice()).unwrap();
+        assert_eq!(ERROR, parsed_error.message_type);
+        assert_eq!(Some(3), parsed_error.reply_serial);
+        assert_eq!(Some(":1.2"), parsed_error.destination.as_deref());
+        assert_eq!(Some("org.freedesktop.DBus.Error.UnknownMethod"), parsed_error.error_name.as_deref());
+        assert_eq!("No such method Foo", parsed_error.body_reader().read_string().unwrap());
+    }
+
+    #[test]
+    fn test_message_without_body_round_trip()
+    {
+        let mut message = Message::signal("/io/github/luckboy/Mntdf", "io.github.luckboy.Mntdf", "Changed");
+        message.serial = 2;
+        let bytes = message.to_bytes();
+        assert_eq!(0, bytes.len() % 8);
+        let parsed_message = Message::from_bytes(bytes.as_slice()).unwrap();
+        assert_eq!(SIGNAL, parsed_message.message_type);
+        assert_eq!("", parsed_message.signature);
+        assert!(parsed_message.body.is_empty());
+    }
+
+    #[test]
+    fn test_empty_array_is_padded_to_element_alignment()
+    {
+        let mut writer = MessageWriter::new();
+        writer.write_byte(1);
+        let positions = writer.begin_array(8);
+        writer.end_array(positions);
+        writer.write_byte(2);
+        // The length is aligned to 4 and the padding to the first element is present even if the
+        // array is empty.
+        assert_eq!(vec![1, 0, 0, 0, 0, 0, 0, 0, 2], writer.buf);
+    }
+
+    #[test]
+    fn test_struct_array_alignment()
+    {
+        let mut writer = MessageWriter::new();
+        writer.write_u32(1);
+        let positions = writer.begin_array(8);
+        for i in 0..2 {
+            writer.begin_struct();
+            writer.write_string("a");
+            writer.write_u64(i);
+            writer.write_i64(-1);
+            writer.write_u32(5);
+        }
+        writer.end_array(positions);
+        let buf = writer.buf;
+        // Each structure is aligned to 8: the string takes 6 bytes, the 64-bit numbers start at 16
+        // and 24, and the 32-bit number ends at 36.
+        assert_eq!(1, read_u32_at(buf.as_slice(), 0));
+        assert_eq!(60, read_u32_at(buf.as_slice(), 4));
+        assert_eq!(1, read_u32_at(buf.as_slice(), 8));
+        assert_eq!(0, u64::from_le_bytes(buf[16..24].try_into().unwrap()));
+        assert_eq!(-1, i64::from_le_bytes(buf[24..32].try_into().unwrap()));
+        assert_eq!(5, read_u32_at(buf.as_slice(), 32));
+        assert_eq!(1, read_u32_at(buf.as_slice(), 40));
+        assert_eq!(1, u64::from_le_bytes(buf[48..56].try_into().unwrap()));
+        assert_eq!(68, buf.len());
+    }
+
+    #[test]
+    fn test_big_endian_message_is_parsed()
+    {
+        let mut bytes: Vec<u8> = vec![b'B', SIGNAL, 0, 1];
+        bytes.extend_from_slice(&4u32.to_be_bytes());
+        bytes.extend_from_slice(&9u32.to_be_bytes());
+        // The array of header fields contains only the signature field.
+        bytes.extend_from_slice(&7u32.to_be_bytes());
+        bytes.extend_from_slice(&[SIGNATURE_FIELD, 1, b'g', 0, 1, b'u', 0, 0]);
+        bytes.extend_from_slice(&42u32.to_be_bytes());
+        assert_eq!(Some(bytes.len()), message_len(bytes.as_slice()));
+        let message = Message::from_bytes(bytes.as_slice()).unwrap();
+        assert_eq!(9, message.serial);
+        assert_eq!("u", message.signature);
+        assert_eq!(42, message.body_reader().read_u32().unwrap());
+    }
+
+    #[test]
+    fn test_truncated_message_is_rejected()
+    {
+        let mut message = Message::signal("/", "a.b", "C");
+        message.serial = 1;
+        let bytes = message.to_bytes();
+        assert_eq!(None, message_len(&bytes[..15]));
+        assert!(Message::from_bytes(&bytes[..(bytes.len() - 1)]).is_err());
+        assert!(Message::from_bytes(b"x").is_err());
+    }
+
+    #[test]
+    fn test_call_keeps_other_messages()
+    {
+        let (stream, mut peer) = UnixStream::pair().unwrap();
+        let mut conn = Connection::new(stream);
+        let mut signal = Message::signal("/", "a.b", "C");
+        signal.serial = 1;
+        let mut reply = Message { message_type: METHOD_RETURN, serial: 2, reply_serial: Some(1), ..Message::default() };
+        let mut other_signal = Message::signal("/", "a.b", "D");
+        other_signal.serial = 3;
+        let mut data = signal.to_bytes();
+        data.extend(reply.to_bytes());
+        data.extend(other_signal.to_bytes());
+        peer.write_all(data.as_slice()).unwrap();
+        reply = conn.call(Message::method_call("a.b", "/", "a.b", "M")).unwrap();
+        assert_eq!(Some(1), reply.reply_serial);
+        assert!(conn.has_messages());
+        let members: Vec<Option<String>> = conn.receive().unwrap().into_iter().map(|message| message.member).collect();
+        assert_eq!(vec![Some(String::from("C")), Some(String::from("D"))], members);
+        assert!(!conn.has_messages());
+    }
+
+    #[test]
+    fn test_unescape_address_value()
+    {
+        assert_eq!("/run/user/1000/bus", unescape_address_value("/run/user/1000/bus"));
+        assert_eq!("/tmp/a b", unescape_address_value("/tmp/a%20b"));
+        assert_eq!("%zz", unescape_address_value("%zz"));
+    }
+}
@@ -0,0 +1,92 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::*;
+
+/// Identifiers of block devices from the `/dev/disk/by-uuid` and `/dev/disk/by-label`
+/// directories.
+pub struct DiskIds
+{
+    uuids: Vec<(u64, String)>,
+    labels: Vec<(u64, String)>,
+}
+
+/// Decodes escape sequences such as `\x20` which are used by udev in names of symbolic links.
+fn unescape_udev_name(s: &str) -> String
+{
+    let bytes = s.as_bytes();
+    let mut unescaped_bytes: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = if bytes[i] == b'\\' && i + 3 < bytes.len() && bytes[i + 1] == b'x' {
+            std::str::from_utf8(&bytes[(i + 2)..(i + 4)]).ok().and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        } else {
+            None
+        };
+        match hex {
+            Some(b) => {
+                unescaped_bytes.push(b);
+                i += 4;
+            },
+            None => {
+                unescaped_bytes.push(bytes[i]);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(unescaped_bytes.as_slice()).into_owned()
+}
+
+fn read_disk_dir<P: AsRef<Path>>(path: P) -> Vec<(u64, String)>
+{
+    let mut ids: Vec<(u64, String)> = Vec::new();
+    if let Ok(dir) = fs::read_dir(path) {
+        for entry in dir.flatten() {
+            // Symbolic links are followed to get the device numbers of the devices.
+            if let Ok(metadata) = fs::metadata(entry.path()) {
+                ids.push((metadata.rdev(), unescape_udev_name(&entry.file_name().to_string_lossy())));
+            }
+        }
+    }
+    ids
+}
+
+impl DiskIds
+{
+    /// Reads the identifiers from the `by-uuid` and `by-label` subdirectories of the directory.
+    /// Missing directories are treated as empty.
+    pub fn read<P: AsRef<Path>>(disk_dir: P) -> DiskIds
+    {
+        DiskIds {
+            uuids: read_disk_dir(disk_dir.as_ref().join("by-uuid")),
+            labels: read_disk_dir(disk_dir.as_ref().join("by-label")),
+        }
+    }
+
+    /// Returns the UUID of the device that has the specified device number.
+    pub fn uuid(&self, rdev: u64) -> Option<&str>
+    {
+        self.uuids.iter().find(|pair| pair.0 == rdev).map(|pair| pair.1.as_str())
+    }
+
+    /// Returns the label of the device that has the specified device number.
+    pub fn label(&self, rdev: u64) -> Option<&str>
+    {
+        self.labels.iter().find(|pair| pair.0 == rdev).map(|pair| pair.1.as_str())
+    }
+}
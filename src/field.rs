@@ -0,0 +1,132 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::result;
+use crate::i18n::gettext_fmt;
+
+/// A field (column) of the output table.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Field
+{
+    Source,
+    FsType,
+    ITotal,
+    IUsed,
+    IAvail,
+    IPcent,
+    Size,
+    Used,
+    Avail,
+    Pcent,
+    Target,
+    File,
+    Reserved,
+    Options,
+    Flags,
+    Fsid,
+    NameMax,
+    RawFlags,
+    MajMin,
+    Uuid,
+    Label,
+    Backing,
+    UpperDir,
+    LowerDir,
+    Server,
+    Export,
+    Propagation,
+    Id,
+    Parent,
+    Subvol,
+    SubvolId,
+    Bar,
+    FullIn,
+}
+
+const FIELDS: &[(&str, Field)] = &[
+    ("source", Field::Source),
+    ("fstype", Field::FsType),
+    ("itotal", Field::ITotal),
+    ("iused", Field::IUsed),
+    ("iavail", Field::IAvail),
+    ("ipcent", Field::IPcent),
+    ("size", Field::Size),
+    ("used", Field::Used),
+    ("avail", Field::Avail),
+    ("pcent", Field::Pcent),
+    ("target", Field::Target),
+    ("file", Field::File),
+    ("reserved", Field::Reserved),
+    ("options", Field::Options),
+    ("flags", Field::Flags),
+    ("fsid", Field::Fsid),
+    ("namemax", Field::NameMax),
+    ("rawflags", Field::RawFlags),
+    ("majmin", Field::MajMin),
+    ("uuid", Field::Uuid),
+    ("label", Field::Label),
+    ("backing", Field::Backing),
+    ("upperdir", Field::UpperDir),
+    ("lowerdir", Field::LowerDir),
+    ("server", Field::Server),
+    ("export", Field::Export),
+    ("propagation", Field::Propagation),
+    ("id", Field::Id),
+    ("parent", Field::Parent),
+    ("subvol", Field::Subvol),
+    ("subvolid", Field::SubvolId),
+    ("bar", Field::Bar),
+    ("fullin", Field::FullIn),
+];
+
+impl Field
+{
+    /// Returns the field that has the specified name.
+    pub fn from_name(name: &str) -> Option<Field>
+    {
+        FIELDS.iter().find(|pair| pair.0 == name).map(|pair| pair.1)
+    }
+
+    /// Returns the name of the field.
+    pub fn name(&self) -> &'static str
+    {
+        FIELDS.iter().find(|pair| pair.1 == *self).map(|pair| pair.0).unwrap()
+    }
+
+    /// Returns `true` if the column of the field is aligned to the left, otherwise `false`.
+    pub fn is_left_aligned(&self) -> bool
+    {
+        matches!(self, Field::Source | Field::FsType | Field::Target | Field::File | Field::Options | Field::Flags | Field::Fsid | Field::Uuid | Field::Label | Field::Backing | Field::UpperDir | Field::LowerDir | Field::Server | Field::Export | Field::Propagation | Field::Subvol | Field::Bar)
+    }
+}
+
+/// Parses a comma-separated list of field names which is an argument of the specified option.
+pub fn parse_fields(s: &str, opt_name: &str) -> result::Result<Vec<Field>, String>
+{
+    let mut fields: Vec<Field> = Vec::new();
+    for name in s.split(',') {
+        match Field::from_name(name) {
+            Some(field) => {
+                if fields.contains(&field) {
+                    return Err(gettext_fmt("option {}: field {} used more than once", &[format!("--{}", opt_name).as_str(), format!("{:?}", name).as_str()]));
+                }
+                fields.push(field);
+            },
+            None => return Err(gettext_fmt("option {}: field {} unknown", &[format!("--{}", opt_name).as_str(), format!("{:?}", name).as_str()])),
+        }
+    }
+    Ok(fields)
+}
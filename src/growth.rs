@@ -0,0 +1,190 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::time::Duration;
+
+/// The maximal number of samples of a filesystem which are kept in watch mode.
+const MAX_SAMPLE_COUNT: usize = 60;
+
+/// Samples of used space of filesystems. A sample is a time in seconds since the Unix epoch with
+/// used space in bytes.
+pub struct UsageSamples
+{
+    samples: Vec<(String, Vec<(f64, f64)>)>,
+}
+
+impl UsageSamples
+{
+    pub fn new() -> UsageSamples
+    {
+        UsageSamples { samples: Vec::new(), }
+    }
+
+    /// Returns the samples of the filesystem mounted on the mount point.
+    pub fn samples(&self, mount_point: &str) -> &[(f64, f64)]
+    {
+        self.samples.iter().find(|pair| pair.0 == mount_point).map(|pair| pair.1.as_slice()).unwrap_or(&[])
+    }
+
+    /// Adds a sample of the filesystem mounted on the mount point. The oldest sample is removed if
+    /// there are too many samples.
+    pub fn add(&mut self, mount_point: &str, time: f64, used: f64)
+    {
+        let index = match self.samples.iter().position(|pair| pair.0 == mount_point) {
+            Some(index) => index,
+            None => {
+                self.samples.push((String::from(mount_point), Vec::new()));
+                self.samples.len() - 1
+            },
+        };
+        let samples = &mut self.samples[index].1;
+        if samples.len() >= MAX_SAMPLE_COUNT {
+            samples.remove(0);
+        }
+        samples.push((time, used));
+    }
+}
+
+/// Estimates a growth rate in bytes per second by the least squares method. `None` is returned if
+/// there are less than two samples at different times.
+pub fn growth_rate(samples: &[(f64, f64)]) -> Option<f64>
+{
+    if samples.len() < 2 {
+        return None;
+    }
+    let count = samples.len() as f64;
+    let mean_time = samples.iter().map(|sample| sample.0).sum::<f64>() / count;
+    let mean_used = samples.iter().map(|sample| sample.1).sum::<f64>() / count;
+    let covariance: f64 = samples.iter().map(|sample| (sample.0 - mean_time) * (sample.1 - mean_used)).sum();
+    let variance: f64 = samples.iter().map(|sample| (sample.0 - mean_time) * (sample.0 - mean_time)).sum();
+    if variance > 0.0 { Some(covariance / variance) } else { None }
+}
+
+/// Returns the estimated number of seconds until a filesystem is full, or `None` if the used
+/// space doesn't grow.
+pub fn time_until_full(samples: &[(f64, f64)], available: i64) -> Option<u64>
+{
+    match growth_rate(samples) {
+        Some(_) if available <= 0   => Some(0),
+        Some(rate) if rate > 0.0    => Some((available as f64 / rate).min(u64::MAX as f64) as u64),
+        _                           => None,
+    }
+}
+
+/// Formats a number of seconds with the two largest units, for example `3d12h` or `5m`.
+pub fn format_duration(seconds: u64) -> String
+{
+    let units: [(u64, &str); 4] = [(86400, "d"), (3600, "h"), (60, "m"), (1, "s")];
+    for (i, (unit, suffix)) in units.iter().enumerate() {
+        if seconds >= *unit || *unit == 1 {
+            let count = seconds / unit;
+            return match units.get(i + 1) {
+                Some((next_unit, next_suffix)) if seconds % unit >= *next_unit => format!("{}{}{}{}", count, suffix, (seconds % unit) / next_unit, next_suffix),
+                _ => format!("{}{}", count, suffix),
+            };
+        }
+    }
+    String::from("0s")
+}
+
+/// Parses a duration which is a number of seconds with an optional `s`, `m`, `h`, `d` or `w`
+/// suffix, for example `7d`.
+pub fn parse_duration(s: &str) -> Option<Duration>
+{
+    let (number, unit) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1.0),
+        Some((i, 'm')) => (&s[..i], 60.0),
+        Some((i, 'h')) => (&s[..i], 3600.0),
+        Some((i, 'd')) => (&s[..i], 86400.0),
+        Some((i, 'w')) => (&s[..i], 604800.0),
+        _              => (s, 1.0),
+    };
+    match number.parse::<f64>() {
+        Ok(number) => Duration::try_from_secs_f64(number * unit).ok(),
+        Err(_)     => None,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_growth_rate_requires_two_samples_at_different_times()
+    {
+        assert_eq!(None, growth_rate(&[]));
+        assert_eq!(None, growth_rate(&[(0.0, 100.0)]));
+        assert_eq!(None, growth_rate(&[(10.0, 100.0), (10.0, 200.0)]));
+    }
+
+    #[test]
+    fn test_growth_rate_estimates_slope()
+    {
+        assert_eq!(Some(2.0), growth_rate(&[(0.0, 100.0), (10.0, 120.0), (20.0, 140.0)]));
+        assert_eq!(Some(-1.0), growth_rate(&[(0.0, 100.0), (10.0, 90.0)]));
+        assert_eq!(Some(0.0), growth_rate(&[(0.0, 100.0), (10.0, 100.0)]));
+    }
+
+    #[test]
+    fn test_time_until_full()
+    {
+        let samples = [(0.0, 100.0), (10.0, 200.0)];
+        assert_eq!(Some(50), time_until_full(&samples, 500));
+        assert_eq!(Some(0), time_until_full(&samples, 0));
+        assert_eq!(Some(0), time_until_full(&samples, -10));
+        assert_eq!(None, time_until_full(&[(0.0, 200.0), (10.0, 100.0)], 500));
+        assert_eq!(None, time_until_full(&[(0.0, 100.0), (10.0, 100.0)], 500));
+        assert_eq!(None, time_until_full(&[(0.0, 100.0)], 0));
+    }
+
+    #[test]
+    fn test_format_duration()
+    {
+        assert_eq!("0s", format_duration(0));
+        assert_eq!("59s", format_duration(59));
+        assert_eq!("1m1s", format_duration(61));
+        assert_eq!("1h", format_duration(3600));
+        assert_eq!("1h1m", format_duration(3661));
+        assert_eq!("3d12h", format_duration(302400));
+    }
+
+    #[test]
+    fn test_parse_duration_parses_suffixes()
+    {
+        assert_eq!(Some(Duration::from_secs(30)), parse_duration("30"));
+        assert_eq!(Some(Duration::from_secs(30)), parse_duration("30s"));
+        assert_eq!(Some(Duration::from_secs(120)), parse_duration("2m"));
+        assert_eq!(Some(Duration::from_secs(5400)), parse_duration("1.5h"));
+        assert_eq!(Some(Duration::from_secs(604800)), parse_duration("7d"));
+        assert_eq!(Some(Duration::from_secs(1209600)), parse_duration("2w"));
+        assert_eq!(Some(Duration::from_millis(500)), parse_duration("0.5"));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_invalid_durations()
+    {
+        assert_eq!(None, parse_duration(""));
+        assert_eq!(None, parse_duration("s"));
+        assert_eq!(None, parse_duration("-1"));
+        assert_eq!(None, parse_duration("1x"));
+        assert_eq!(None, parse_duration("1 d"));
+        assert_eq!(None, parse_duration("nan"));
+        assert_eq!(None, parse_duration("inf"));
+        assert_eq!(None, parse_duration("1e300"));
+        assert_eq!(None, parse_duration("1e15w"));
+    }
+}
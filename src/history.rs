@@ -0,0 +1,264 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::path::*;
+use std::result;
+use std::time::*;
+use crate::args::{Opt, OptionDesc, Parser, print_option_help};
+use crate::args::OptName::*;
+use crate::config::data_path;
+use crate::field::Field;
+use crate::i18n::gettext;
+use crate::size::*;
+use crate::snapshot::print_rows;
+use crate::sqlite::*;
+use crate::unit::*;
+
+const HISTORY_OPTIONS: &[OptionDesc] = &[
+    OptionDesc { short_name: Some('B'), long_name: Some("block-size"), arg_name: Some("SIZE"), is_arg_optional: false, description: "scale sizes by SIZE, for example 1M or 4KiB", },
+    OptionDesc { short_name: None, long_name: Some("db"), arg_name: Some("DB"), is_arg_optional: false, description: "read records from the SQLite database DB instead of the default database", },
+    OptionDesc { short_name: Some('H'), long_name: Some("si"), arg_name: None, is_arg_optional: false, description: "print sizes in powers of 1000, for example 1.1G", },
+    OptionDesc { short_name: Some('h'), long_name: Some("human-readable"), arg_name: None, is_arg_optional: false, description: "print sizes in powers of 1024, for example 1.0G", },
+    OptionDesc { short_name: Some('k'), long_name: None, arg_name: None, is_arg_optional: false, description: "use 1024-byte blocks", },
+    OptionDesc { short_name: Some('n'), long_name: Some("lines"), arg_name: Some("NUM"), is_arg_optional: false, description: "print only the last NUM records", },
+    OptionDesc { short_name: None, long_name: Some("help"), arg_name: None, is_arg_optional: false, description: "display this help and exit", },
+];
+
+const CREATE_SQL: &str = "CREATE TABLE IF NOT EXISTS usage (
+    time INTEGER NOT NULL,
+    source TEXT NOT NULL,
+    fstype TEXT NOT NULL,
+    target TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    used INTEGER NOT NULL,
+    avail INTEGER NOT NULL,
+    itotal INTEGER NOT NULL,
+    iused INTEGER NOT NULL,
+    iavail INTEGER NOT NULL
+)";
+
+const CREATE_INDEX_SQL: &str = "CREATE INDEX IF NOT EXISTS usage_target_time ON usage (target, time)";
+
+const INSERT_SQL: &str = "INSERT INTO usage (time, source, fstype, target, size, used, avail, itotal, iused, iavail) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+
+/// The last records are selected in the descending order and then they are reversed.
+const SELECT_SQL: &str = "SELECT time, size, used, avail FROM usage WHERE target = ? ORDER BY time DESC LIMIT ?";
+
+const SELECT_SAMPLES_SQL: &str = "SELECT time, used FROM usage WHERE target = ? ORDER BY time DESC LIMIT ?";
+
+/// The maximal number of the last records which are used to estimate growth.
+const MAX_SAMPLE_COUNT: i64 = 1000;
+
+/// Returns the path of the default database, that is `history.db` in the `mntdf` data directory.
+pub fn default_database_path() -> Option<PathBuf>
+{
+    data_path("history.db")
+}
+
+/// Opens a database for recording and creates the table if it doesn't exist.
+fn open_record_database(path: &Path) -> result::Result<Database, String>
+{
+    let database = Database::open(path, true)?;
+    database.execute(CREATE_SQL)?;
+    database.execute(CREATE_INDEX_SQL)?;
+    Ok(database)
+}
+
+fn insert_usage_entries(database: &Database, usage_entries: &[crate::UsageEntry], time: i64) -> result::Result<(), String>
+{
+    let mut stmt = database.prepare(INSERT_SQL)?;
+    for usage_entry in usage_entries.iter() {
+        stmt.bind_int64(1, time)?;
+        stmt.bind_text(2, usage_entry.file_system.as_str())?;
+        stmt.bind_text(3, usage_entry.file_system_type.as_str())?;
+        stmt.bind_text(4, usage_entry.mount_point.as_str())?;
+        stmt.bind_int64(5, usage_entry.total_bytes as i64)?;
+        stmt.bind_int64(6, usage_entry.used_bytes as i64)?;
+        stmt.bind_int64(7, usage_entry.available_bytes)?;
+        stmt.bind_int64(8, usage_entry.total_inodes as i64)?;
+        stmt.bind_int64(9, usage_entry.used_inodes as i64)?;
+        stmt.bind_int64(10, usage_entry.available_inodes as i64)?;
+        stmt.step()?;
+    }
+    Ok(())
+}
+
+/// Appends a row with the current time for each filesystem to the `usage` table of an SQLite
+/// database. The database and the table are created if they don't exist. The rows are inserted
+/// in one transaction.
+pub fn record_usage(path: &Path, usage_entries: &[crate::UsageEntry]) -> result::Result<(), String>
+{
+    // The directory of the default database can not exist yet.
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    let database = open_record_database(path)?;
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs() as i64).unwrap_or(0);
+    database.execute("BEGIN")?;
+    match insert_usage_entries(&database, usage_entries, time) {
+        Ok(()) => database.execute("COMMIT"),
+        Err(err) => {
+            let _ = database.execute("ROLLBACK");
+            Err(err)
+        },
+    }
+}
+
+const SELECT_TABLE_SQL: &str = "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = 'usage'";
+
+/// Opens a database for reading recorded samples. `None` is returned if the database or the table
+/// doesn't exist yet. The database isn't created or changed.
+pub fn open_sample_database(path: &Path) -> result::Result<Option<Database>, String>
+{
+    if !path.exists() {
+        return Ok(None);
+    }
+    let database = Database::open(path, false)?;
+    let is_table = {
+        let mut stmt = database.prepare(SELECT_TABLE_SQL)?;
+        stmt.step()? && stmt.column_int64(0) > 0
+    };
+    Ok(if is_table { Some(database) } else { None })
+}
+
+/// Returns the recorded samples of used space of the filesystem mounted on the mount point as
+/// times in seconds since the Unix epoch with used space in bytes.
+pub fn recorded_samples(database: &Database, mount_point: &str) -> result::Result<Vec<(f64, f64)>, String>
+{
+    let mut stmt = database.prepare(SELECT_SAMPLES_SQL)?;
+    stmt.bind_text(1, mount_point)?;
+    stmt.bind_int64(2, MAX_SAMPLE_COUNT)?;
+    let mut samples: Vec<(f64, f64)> = Vec::new();
+    while stmt.step()? {
+        samples.push((stmt.column_int64(0) as f64, stmt.column_int64(1) as f64));
+    }
+    // The samples are selected in the descending order.
+    samples.reverse();
+    Ok(samples)
+}
+
+fn print_history(path: &Path, target: &str, line_count: i64, opts: &crate::Options) -> result::Result<(), String>
+{
+    let database = Database::open(path, false)?;
+    let mut stmt = database.prepare(SELECT_SQL)?;
+    stmt.bind_text(1, target)?;
+    stmt.bind_int64(2, line_count)?;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    while stmt.step()? {
+        let used = stmt.column_int64(2).max(0) as u64;
+        let available = stmt.column_int64(3);
+        let percent = crate::capacity_percent(used, i128::from(available), crate::percent_rounding(opts)).map(|percent| format!("{}%", percent)).unwrap_or_else(|| String::from("-"));
+        rows.push(vec![
+                crate::format_local_time(stmt.column_int64(0) as libc::time_t),
+                crate::format_size(stmt.column_int64(1).max(0) as u64, true, opts.size_unit, opts),
+                crate::format_size(used, true, opts.size_unit, opts),
+                crate::format_signed_size(available, false, opts.size_unit, opts),
+                percent,
+        ]);
+    }
+    if rows.is_empty() {
+        return Err(format!("{}: {}", target, gettext("No records")));
+    }
+    let mut header = vec![String::from(gettext("Time"))];
+    header.extend([Field::Size, Field::Used, Field::Avail, Field::Pcent].iter().map(|field| crate::field_header(*field, opts)));
+    rows.push(header);
+    rows.reverse();
+    print_rows(rows.as_slice());
+    Ok(())
+}
+
+fn print_history_help(program_name: &str)
+{
+    println!("Usage: {} history [OPTION]... TARGET", program_name);
+    println!("Print usage of the filesystem mounted on TARGET which is recorded by the --record");
+    println!("option. The default database is history.db in $XDG_DATA_HOME/mntdf.");
+    println!();
+    println!("Options:");
+    print_option_help(HISTORY_OPTIONS);
+}
+
+/// Runs the `history` subcommand and returns an exit status. The first argument should be the
+/// subcommand name.
+pub fn history_main(program_name: &str, args: &[String]) -> i32
+{
+    let mut opt_parser = Parser::new(args, HISTORY_OPTIONS);
+    let mut opts = crate::Options::new();
+    let mut path: Option<PathBuf> = None;
+    let mut line_count = i64::MAX;
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt(Short('B'), Some(opt_arg)))) => crate::set_block_size(&mut opts, opt_arg.as_str()),
+            Some(Ok(Opt(Long("db"), Some(opt_arg)))) => path = Some(PathBuf::from(opt_arg)),
+            Some(Ok(Opt(Short('h'), _))) => opts.size_unit = Some(SizeUnit::Human(BINARY_BASE)),
+            Some(Ok(Opt(Short('H'), _))) => opts.size_unit = Some(SizeUnit::Human(SI_BASE)),
+            Some(Ok(Opt(Short('k'), _))) => {
+                opts.kilo_flag = true;
+                opts.size_unit = None;
+            },
+            Some(Ok(Opt(Short('n'), Some(opt_arg)))) => {
+                match opt_arg.parse::<i64>() {
+                    Ok(count) if count >= 0 => line_count = count,
+                    _ => {
+                        eprintln!("{} -- {:?}", gettext("invalid number"), opt_arg);
+                        return 1;
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("help"), _))) => {
+                print_history_help(program_name);
+                return 0;
+            },
+            Some(Ok(Opt(name, _))) => {
+                eprintln!("{} -- {:?}", gettext("unknown option"), name);
+                return 1;
+            },
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    if opts.size_unit.is_none() && !opts.kilo_flag {
+        opts.size_unit = env_size_unit();
+    }
+    let target = match opt_parser.operands() {
+        [target] => target.as_str(),
+        [] => {
+            eprintln!("{}", gettext("missing operand"));
+            return 1;
+        },
+        _ => {
+            eprintln!("{}", gettext("too many arguments"));
+            return 1;
+        },
+    };
+    let path = match path.or_else(default_database_path) {
+        Some(path) => path,
+        None => {
+            eprintln!("{}", gettext("Can't determine the data directory"));
+            return 1;
+        },
+    };
+    match print_history(path.as_path(), target, line_count, &opts) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}: {}", path.to_string_lossy(), err);
+            1
+        },
+    }
+}
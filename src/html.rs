@@ -0,0 +1,45 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+/// Escapes special characters of HTML in a text or an attribute value.
+pub fn html_escape(s: &str) -> String
+{
+    let mut t = String::new();
+    for c in s.chars() {
+        match c {
+            '&'  => t.push_str("&amp;"),
+            '<'  => t.push_str("&lt;"),
+            '>'  => t.push_str("&gt;"),
+            '"'  => t.push_str("&quot;"),
+            '\'' => t.push_str("&#39;"),
+            c    => t.push(c),
+        }
+    }
+    t
+}
+
+/// Returns an inline style of a table cell that shows usage as a bar in the cell background. The
+/// bar is green, yellow or red depending on the usage.
+pub fn html_usage_style(percent: u64) -> String
+{
+    let color = match percent {
+        0..=79  => "#9e9",
+        80..=89 => "#ee6",
+        _       => "#e77",
+    };
+    format!("background: linear-gradient(to right, {} {}%, transparent {}%)", color, percent.min(100), percent.min(100))
+}
@@ -0,0 +1,167 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use std::sync::OnceLock;
+
+/// A message catalog of a language, which maps messages to their translations.
+struct Catalog
+{
+    language: &'static str,
+    messages: &'static [(&'static str, &'static str)],
+}
+
+const CATALOGS: &[Catalog] = &[
+    Catalog {
+        language: "pl",
+        messages: &[
+            ("Filesystem", "System plików"),
+            ("Type", "Typ"),
+            ("Inodes", "I-węzły"),
+            ("IUsed", "IUżyte"),
+            ("IFree", "IWolne"),
+            ("IUse%", "IUż%"),
+            ("Size", "Rozm."),
+            ("Used", "Użyte"),
+            ("Avail", "Dost."),
+            ("Available", "Dostępne"),
+            ("Use%", "%uż."),
+            ("Capacity", "Pojemność"),
+            ("Mounted on", "Zamont. na"),
+            ("File", "Plik"),
+            ("Reserved", "Zarezerw."),
+            ("Options", "Opcje"),
+            ("Flags", "Flagi"),
+            ("NameMax", "MaksNazwa"),
+            ("RawFlags", "SuroweFlagi"),
+            ("Label", "Etykieta"),
+            ("Backing file", "Plik bazowy"),
+            ("Upper dir", "Katalog górny"),
+            ("Lower dirs", "Katalogi dolne"),
+            ("Server", "Serwer"),
+            ("Export", "Eksport"),
+            ("Propagation", "Propagacja"),
+            ("Parent", "Rodzic"),
+            ("Subvolume", "Podwolumin"),
+            ("Subvol ID", "ID podwol."),
+            ("Usage", "Użycie"),
+            ("Full in", "Pełny za"),
+            ("Time", "Czas"),
+            ("-blocks", "-bloki"),
+            ("Can't find mount entry", "Nie można znaleźć wpisu montowania"),
+            ("Can't find mount table", "Nie można znaleźć tablicy montowań"),
+            ("Can't determine the data directory", "Nie można ustalić katalogu danych"),
+            ("Daemon is already running", "Demon jest już uruchomiony"),
+            ("Socket is owned by another user", "Gniazdo należy do innego użytkownika"),
+            ("Invalid snapshot", "Nieprawidłowa migawka"),
+            ("Device isn't mounted", "Urządzenie nie jest zamontowane"),
+            ("No records", "Brak zapisów"),
+            ("No such process", "Nie ma takiego procesu"),
+            ("expected string", "oczekiwano łańcucha znaków"),
+            ("invalid bar width", "nieprawidłowa szerokość paska"),
+            ("invalid bind mount mode", "nieprawidłowy tryb montowań wiązanych"),
+            ("invalid block size", "nieprawidłowy rozmiar bloku"),
+            ("invalid bus", "nieprawidłowa magistrala"),
+            ("invalid color mode", "nieprawidłowy tryb kolorów"),
+            ("invalid compatibility mode", "nieprawidłowy tryb zgodności"),
+            ("invalid interval", "nieprawidłowy odstęp czasu"),
+            ("invalid log target", "nieprawidłowy cel logowania"),
+            ("invalid number", "nieprawidłowa liczba"),
+            ("invalid percentage", "nieprawidłowa wartość procentowa"),
+            ("invalid summary key", "nieprawidłowy klucz podsumowania"),
+            ("invalid PID", "nieprawidłowy PID"),
+            ("invalid precision", "nieprawidłowa precyzja"),
+            ("invalid rounding mode", "nieprawidłowy tryb zaokrąglania"),
+            ("invalid Zabbix mode", "nieprawidłowy tryb Zabbiksa"),
+            ("unknown option", "nieznana opcja"),
+            ("option is ambiguous", "opcja jest niejednoznaczna"),
+            ("option requires an argument", "opcja wymaga argumentu"),
+            ("option doesn't allow an argument", "opcja nie przyjmuje argumentu"),
+            ("options {} and {} are mutually exclusive", "opcje {} i {} wykluczają się wzajemnie"),
+            ("option {} requires a file", "opcja {} wymaga pliku"),
+            ("option {} requires {}", "opcja {} wymaga {}"),
+            ("options {} and {} require {}, {} or {}", "opcje {} i {} wymagają {}, {} lub {}"),
+            ("option {} requires {} or {}", "opcja {} wymaga {} lub {}"),
+            ("option {} can be used only with the table output format", "opcja {} może być użyta tylko z formatem wyjścia w postaci tabeli"),
+            ("option {}: field {} unknown", "opcja {}: nieznane pole {}"),
+            ("option {}: field {} used more than once", "opcja {}: pole {} użyte więcej niż raz"),
+            ("option {}: unit {} invalid", "opcja {}: nieprawidłowa jednostka {}"),
+            ("option {}: unterminated placeholder", "opcja {}: niezakończone pole zastępcze"),
+            ("option {}: unmatched '}'", "opcja {}: niedopasowany '}'"),
+            ("missing operand", "brakujący argument"),
+            ("too many arguments", "za dużo argumentów"),
+            ("not mounted", "niezamontowany"),
+            ("not mounted, optional", "niezamontowany, opcjonalny"),
+            ("mounted but not in fstab", "zamontowany, ale nie ma go w fstab"),
+        ],
+    },
+];
+
+/// Returns the name of the locale of messages from the `LC_ALL`, `LC_MESSAGES` or `LANG`
+/// environment variable.
+fn messages_locale() -> Option<String>
+{
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter().filter_map(|name| env::var(name).ok()).find(|value| !value.is_empty())
+}
+
+/// Finds the catalog for the locale of messages as gettext does. The `LANGUAGE` environment
+/// variable is a list of languages separated by colons and it takes precedence over the locale
+/// unless the locale is the C locale.
+fn find_catalog() -> Option<&'static Catalog>
+{
+    let locale = messages_locale()?;
+    if locale == "C" || locale == "POSIX" || locale.starts_with("C.") {
+        return None;
+    }
+    let languages = env::var("LANGUAGE").ok().filter(|value| !value.is_empty()).unwrap_or(locale);
+    for language in languages.split(':') {
+        // A language can be followed by a codeset and a modifier, for example pl_PL.UTF-8@euro.
+        let language = language.split(['.', '@']).next().unwrap_or("");
+        let short_language = language.split('_').next().unwrap_or("");
+        let catalog = CATALOGS.iter().find(|catalog| catalog.language == language)
+            .or_else(|| CATALOGS.iter().find(|catalog| catalog.language == short_language));
+        if catalog.is_some() {
+            return catalog;
+        }
+    }
+    None
+}
+
+fn catalog() -> Option<&'static Catalog>
+{
+    static CATALOG: OnceLock<Option<&'static Catalog>> = OnceLock::new();
+    *CATALOG.get_or_init(find_catalog)
+}
+
+/// Translates a message to the language of the locale. The message is returned if it doesn't
+/// have a translation.
+pub fn gettext(msgid: &'static str) -> &'static str
+{
+    catalog().and_then(|catalog| catalog.messages.iter().find(|pair| pair.0 == msgid)).map(|pair| pair.1).unwrap_or(msgid)
+}
+
+/// Translates a message and replaces each `{}` in the translation by the next argument.
+pub fn gettext_fmt(msgid: &'static str, args: &[&str]) -> String
+{
+    let mut s = String::new();
+    let mut parts = gettext(msgid).split("{}");
+    s.push_str(parts.next().unwrap_or(""));
+    for (i, part) in parts.enumerate() {
+        s.push_str(args.get(i).copied().unwrap_or(""));
+        s.push_str(part);
+    }
+    s
+}
@@ -0,0 +1,41 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+/// Escapes commas, equal signs and spaces in a measurement, a tag key or a tag value of the
+/// InfluxDB line protocol.
+pub fn influx_escape(s: &str) -> String
+{
+    let mut t = String::new();
+    for c in s.chars() {
+        if c == ',' || c == '=' || c == ' ' {
+            t.push('\\');
+        }
+        t.push(c);
+    }
+    t
+}
+
+/// Formats a point in the InfluxDB line protocol. Tags with empty values are omitted because
+/// they aren't allowed. The field values should be already formatted, for example `10i`.
+pub fn influx_line(measurement: &str, tags: &[(&str, &str)], fields: &[(&str, String)], timestamp: u128) -> String
+{
+    let mut line = influx_escape(measurement);
+    for (key, value) in tags.iter().filter(|(_, value)| !value.is_empty()) {
+        line.push_str(format!(",{}={}", influx_escape(key), influx_escape(value)).as_str());
+    }
+    let fields: Vec<String> = fields.iter().map(|(key, value)| format!("{}={}", influx_escape(key), value)).collect();
+    format!("{} {} {}", line, fields.join(","), timestamp)
+}
@@ -0,0 +1,311 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::result;
+use std::str::Chars;
+use crate::value::*;
+
+/// Escapes a string as a JSON string with quotes.
+pub fn json_string(s: &str) -> String
+{
+    let mut t = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"'  => t.push_str("\\\""),
+            '\\' => t.push_str("\\\\"),
+            '\n' => t.push_str("\\n"),
+            '\r' => t.push_str("\\r"),
+            '\t' => t.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(t, "\\u{:04x}", c as u32);
+            },
+            c    => t.push(c),
+        }
+    }
+    t.push('"');
+    t
+}
+
+/// Formats a value as JSON.
+pub fn json_value(value: &Value) -> String
+{
+    match value {
+        Value::Null       => String::from("null"),
+        Value::Integer(x) => format!("{}", x),
+        Value::String(s)  => json_string(s.as_str()),
+    }
+}
+
+/// Formats pairs of names and values as a JSON object in one line.
+pub fn json_object(pairs: &[(&str, Value)]) -> String
+{
+    let members: Vec<String> = pairs.iter().map(|(name, value)| format!("{}:{}", json_string(name), json_value(value))).collect();
+    format!("{{{}}}", members.join(","))
+}
+
+/// A parsed JSON value. A number is kept as its text, so that big integers aren't rounded.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum JsonValue
+{
+    Null,
+    Boolean(bool),
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue
+{
+    /// Returns the value of the member of the object that has the specified name.
+    pub fn get(&self, name: &str) -> Option<&JsonValue>
+    {
+        match self {
+            JsonValue::Object(members) => members.iter().find(|member| member.0 == name).map(|member| &member.1),
+            _                          => None,
+        }
+    }
+}
+
+fn skip_json_whitespace(chars: &mut Peekable<Chars<'_>>)
+{
+    while chars.peek().map(|c| *c == ' ' || *c == '\t' || *c == '\n' || *c == '\r').unwrap_or(false) {
+        chars.next();
+    }
+}
+
+fn parse_json_hex4(chars: &mut Peekable<Chars<'_>>) -> result::Result<u32, String>
+{
+    let mut x = 0;
+    for _ in 0..4 {
+        match chars.next().and_then(|c| c.to_digit(16)) {
+            Some(digit) => x = x * 16 + digit,
+            None        => return Err(String::from("invalid escape sequence")),
+        }
+    }
+    Ok(x)
+}
+
+fn parse_json_string(chars: &mut Peekable<Chars<'_>>) -> result::Result<String, String>
+{
+    let mut s = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(s),
+            Some('\\') => {
+                match chars.next() {
+                    Some('"')  => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/')  => s.push('/'),
+                    Some('b')  => s.push('\u{8}'),
+                    Some('f')  => s.push('\u{c}'),
+                    Some('n')  => s.push('\n'),
+                    Some('r')  => s.push('\r'),
+                    Some('t')  => s.push('\t'),
+                    Some('u')  => {
+                        let mut code = parse_json_hex4(chars)?;
+                        // A character outside the basic plane is a surrogate pair.
+                        // A lone surrogate is replaced and the characters after it are kept.
+                        if (0xd800..0xdc00).contains(&code) {
+                            let mut next_chars = chars.clone();
+                            if next_chars.next() == Some('\\') && next_chars.next() == Some('u') {
+                                let low = parse_json_hex4(&mut next_chars)?;
+                                if (0xdc00..0xe000).contains(&low) {
+                                    code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                                    *chars = next_chars;
+                                }
+                            }
+                        }
+                        s.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    },
+                    _ => return Err(String::from("invalid escape sequence")),
+                }
+            },
+            Some(c) if (c as u32) < 0x20 => return Err(String::from("control character in string")),
+            Some(c) => s.push(c),
+            None    => return Err(String::from("unterminated string")),
+        }
+    }
+}
+
+fn parse_json_value(chars: &mut Peekable<Chars<'_>>) -> result::Result<JsonValue, String>
+{
+    skip_json_whitespace(chars);
+    match chars.peek().copied() {
+        Some('"') => {
+            chars.next();
+            Ok(JsonValue::String(parse_json_string(chars)?))
+        },
+        Some('[') => {
+            chars.next();
+            let mut values: Vec<JsonValue> = Vec::new();
+            skip_json_whitespace(chars);
+            if chars.peek() == Some(&']') {
+                chars.next();
+                return Ok(JsonValue::Array(values));
+            }
+            loop {
+                values.push(parse_json_value(chars)?);
+                skip_json_whitespace(chars);
+                match chars.next() {
+                    Some(',') => (),
+                    Some(']') => return Ok(JsonValue::Array(values)),
+                    _         => return Err(String::from("expected ',' or ']'")),
+                }
+            }
+        },
+        Some('{') => {
+            chars.next();
+            let mut members: Vec<(String, JsonValue)> = Vec::new();
+            skip_json_whitespace(chars);
+            if chars.peek() == Some(&'}') {
+                chars.next();
+                return Ok(JsonValue::Object(members));
+            }
+            loop {
+                skip_json_whitespace(chars);
+                if chars.next() != Some('"') {
+                    return Err(String::from("expected string"));
+                }
+                let name = parse_json_string(chars)?;
+                skip_json_whitespace(chars);
+                if chars.next() != Some(':') {
+                    return Err(String::from("expected ':'"));
+                }
+                members.push((name, parse_json_value(chars)?));
+                skip_json_whitespace(chars);
+                match chars.next() {
+                    Some(',') => (),
+                    Some('}') => return Ok(JsonValue::Object(members)),
+                    _         => return Err(String::from("expected ',' or '}'")),
+                }
+            }
+        },
+        Some(c) if c == '-' || c.is_ascii_digit() => {
+            let mut number = String::new();
+            while let Some(c) = chars.peek().copied().filter(|c| *c == '-' || *c == '+' || *c == '.' || *c == 'e' || *c == 'E' || c.is_ascii_digit()) {
+                number.push(c);
+                chars.next();
+            }
+            Ok(JsonValue::Number(number))
+        },
+        Some(c) if c.is_ascii_alphabetic() => {
+            let mut word = String::new();
+            while let Some(c) = chars.peek().copied().filter(|c| c.is_ascii_alphabetic()) {
+                word.push(c);
+                chars.next();
+            }
+            match word.as_str() {
+                "null"  => Ok(JsonValue::Null),
+                "true"  => Ok(JsonValue::Boolean(true)),
+                "false" => Ok(JsonValue::Boolean(false)),
+                _       => Err(format!("invalid value {:?}", word)),
+            }
+        },
+        Some(c) => Err(format!("unexpected character {:?}", c)),
+        None    => Err(String::from("unexpected end of text")),
+    }
+}
+
+/// Parses a JSON text.
+pub fn parse_json(s: &str) -> result::Result<JsonValue, String>
+{
+    let mut chars = s.chars().peekable();
+    let value = parse_json_value(&mut chars)?;
+    skip_json_whitespace(&mut chars);
+    match chars.next() {
+        Some(c) => Err(format!("unexpected character {:?}", c)),
+        None    => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn string(s: &str) -> JsonValue
+    {
+        JsonValue::String(String::from(s))
+    }
+
+    #[test]
+    fn test_parse_json_parses_values()
+    {
+        assert_eq!(Ok(JsonValue::Null), parse_json("null"));
+        assert_eq!(Ok(JsonValue::Boolean(true)), parse_json(" true "));
+        assert_eq!(Ok(JsonValue::Number(String::from("-18446744073709551616"))), parse_json("-18446744073709551616"));
+        assert_eq!(Ok(JsonValue::Array(Vec::new())), parse_json("[ ]"));
+        assert_eq!(Ok(JsonValue::Object(Vec::new())), parse_json("{ }"));
+        let value = parse_json("{\"a\": [1, \"b\", false], \"c\": {\"d\": null}}").unwrap();
+        assert_eq!(Some(&JsonValue::Array(vec![JsonValue::Number(String::from("1")), string("b"), JsonValue::Boolean(false)])), value.get("a"));
+        assert_eq!(Some(&JsonValue::Null), value.get("c").and_then(|c| c.get("d")));
+        assert_eq!(None, value.get("d"));
+    }
+
+    #[test]
+    fn test_parse_json_parses_escapes()
+    {
+        assert_eq!(Ok(string("\"\\/\u{8}\u{c}\n\r\t")), parse_json("\"\\\"\\\\\\/\\b\\f\\n\\r\\t\""));
+        assert_eq!(Ok(string("\u{e9}\u{20ac}")), parse_json("\"\\u00e9\\u20AC\""));
+        assert_eq!(Ok(string("\u{1f600}")), parse_json("\"\\ud83d\\ude00\""));
+    }
+
+    #[test]
+    fn test_parse_json_replaces_lone_surrogates()
+    {
+        assert_eq!(Ok(string("\u{fffd}")), parse_json("\"\\ud83d\""));
+        assert_eq!(Ok(string("\u{fffd}a")), parse_json("\"\\ud83da\""));
+        assert_eq!(Ok(string("\u{fffd}\n")), parse_json("\"\\ud83d\\n\""));
+        assert_eq!(Ok(string("\u{fffd}A")), parse_json("\"\\ud83d\\u0041\""));
+        assert_eq!(Ok(string("\u{fffd}")), parse_json("\"\\ude00\""));
+    }
+
+    #[test]
+    fn test_parse_json_rejects_invalid_strings()
+    {
+        assert!(parse_json("\"abc").is_err());
+        assert!(parse_json("\"abc\\\"").is_err());
+        assert!(parse_json("\"\\x\"").is_err());
+        assert!(parse_json("\"\\u12\"").is_err());
+        assert!(parse_json("\"\\u12g4\"").is_err());
+        assert!(parse_json("\"a\nb\"").is_err());
+        assert!(parse_json("'a'").is_err());
+    }
+
+    #[test]
+    fn test_parse_json_rejects_malformed_texts()
+    {
+        assert!(parse_json("").is_err());
+        assert!(parse_json("nul").is_err());
+        assert!(parse_json("[1,").is_err());
+        assert!(parse_json("[1 2]").is_err());
+        assert!(parse_json("{\"a\" 1}").is_err());
+        assert!(parse_json("{a: 1}").is_err());
+        assert!(parse_json("{\"a\": 1,}").is_err());
+        assert!(parse_json("{} {}").is_err());
+    }
+
+    #[test]
+    fn test_json_string_round_trip()
+    {
+        let s = "a\"b\\c\nd\u{1}\u{1f600}";
+        assert_eq!("\"a\\\"b\\\\c\\nd\\u0001\u{1f600}\"", json_string(s));
+        assert_eq!(Ok(string(s)), parse_json(json_string(s).as_str()));
+    }
+}
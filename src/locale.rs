@@ -0,0 +1,70 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::ffi::*;
+
+/// Separators of numbers in a locale.
+#[derive(Clone, Debug)]
+pub struct NumericLocale
+{
+    pub decimal_point: String,
+    pub thousands_sep: String,
+}
+
+impl NumericLocale
+{
+    /// Sets the `LC_NUMERIC` category from the environment and returns the separators of numbers.
+    ///
+    /// If the locale can't be set, the separators of the C locale are returned.
+    pub fn from_env() -> NumericLocale
+    {
+        let empty_cstring = CString::new("").unwrap();
+        unsafe { libc::setlocale(libc::LC_NUMERIC, empty_cstring.as_ptr()) };
+        let lconv = unsafe { libc::localeconv() };
+        if lconv.is_null() {
+            return NumericLocale::c();
+        }
+        let decimal_point = unsafe { CStr::from_ptr((*lconv).decimal_point) }.to_string_lossy().into_owned();
+        let thousands_sep = unsafe { CStr::from_ptr((*lconv).thousands_sep) }.to_string_lossy().into_owned();
+        NumericLocale {
+            decimal_point: if decimal_point.is_empty() { String::from(".") } else { decimal_point },
+            thousands_sep,
+        }
+    }
+
+    /// Returns the separators of numbers in the C locale.
+    pub fn c() -> NumericLocale
+    {
+        NumericLocale {
+            decimal_point: String::from("."),
+            thousands_sep: String::new(),
+        }
+    }
+}
+
+/// Sets the `LC_CTYPE` category from the environment and returns `true` if the character encoding
+/// of the locale is UTF-8.
+pub fn is_utf8_locale() -> bool
+{
+    let empty_cstring = CString::new("").unwrap();
+    unsafe { libc::setlocale(libc::LC_CTYPE, empty_cstring.as_ptr()) };
+    let codeset = unsafe { libc::nl_langinfo(libc::CODESET) };
+    if codeset.is_null() {
+        return false;
+    }
+    let codeset = unsafe { CStr::from_ptr(codeset) }.to_string_lossy().to_uppercase();
+    codeset == "UTF-8" || codeset == "UTF8"
+}
@@ -15,47 +15,567 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
 use std::cmp::max;
+use std::cmp::min;
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::env;
 use std::ffi::*;
 use std::fs;
 use std::io::*;
 use std::mem::MaybeUninit;
 use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::AsRawFd;
 use std::path::*;
 use std::process::*;
 use std::result;
-use getopt::Opt;
-use libc;
-use mnt;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use mnt::MntOps;
 use mnt::MountEntry;
 use mnt::MountIter;
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+use args::{Opt, OptionDesc, Parser, print_option_help};
+use args::OptName::*;
+use color::*;
+use compat::*;
+use csv::*;
+use config::*;
+use diskid::*;
+use field::*;
+use growth::*;
+use html::*;
+use i18n::*;
+use influx::*;
+use json::*;
+use locale::*;
+use markdown::*;
+use nagios::*;
+#[cfg(feature = "otlp")]
+use otlp::*;
+use mountinfo::*;
+use pager::Pager;
+use prometheus::*;
+use regex::Regex;
+use shell::*;
+use size::*;
+use statsd::*;
+use syslog::*;
+use template::*;
+use term::*;
+use theme::*;
+use toml::*;
+use tsv::*;
+use unit::*;
+use value::*;
+#[cfg(feature = "xml")]
+use xml::*;
+use yaml::*;
+use zabbix::*;
+
+mod args;
+mod check;
+mod color;
+mod compat;
+mod csv;
+mod daemon;
+#[cfg(feature = "dbus")]
+mod dbus;
+mod config;
+mod diskid;
+mod field;
+mod growth;
+mod html;
+#[cfg(feature = "sqlite")]
+mod history;
+mod i18n;
+mod influx;
+mod json;
+mod locale;
+mod markdown;
+mod nagios;
+#[cfg(feature = "otlp")]
+mod otlp;
+mod mountinfo;
+mod pager;
+mod prometheus;
+mod regex;
+mod shell;
+mod statsd;
+mod syslog;
+mod size;
+mod snapshot;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod template;
+mod term;
+mod theme;
+mod toml;
+mod tsv;
+mod unit;
+mod value;
+#[cfg(feature = "xml")]
+mod xml;
+mod yaml;
+mod zabbix;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum BindMountMode
+{
+    Show,
+    Hide,
+    Annotate,
+}
+
+/// A key of grouping filesystems in a summary.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum SummaryKey
+{
+    FsType,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum OutputFormat
+{
+    Table,
+    Markdown,
+    Html,
+    Null,
+    Records,
+    Template,
+    Json,
+    JsonLines,
+    Csv,
+    Tsv,
+    Yaml,
+    Shell,
+    Prometheus,
+    Influx,
+    ZabbixLld,
+    ZabbixValue(ZabbixMode),
+    Check,
+    #[cfg(feature = "xml")]
+    Xml,
+}
+
+impl OutputFormat
+{
+    /// Returns `true` if the format is for programs rather than people. Structured formats have
+    /// raw values instead of formatted values.
+    fn is_structured(self) -> bool
+    {
+        !matches!(self, OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Html | OutputFormat::Null | OutputFormat::Records | OutputFormat::Template)
+    }
+}
 
 struct Options
 {
+    all_flag: bool,
+    local_flag: bool,
     kilo_flag: bool,
+    size_unit: Option<SizeUnit>,
+    precision: Option<usize>,
+    rounding: Option<Rounding>,
+    group_digits_flag: bool,
+    numeric_locale: NumericLocale,
+    disk_ids: Option<DiskIds>,
+    inode_flag: bool,
+    type_flag: bool,
+    options_flag: bool,
+    mark_read_only_flag: bool,
+    color_flag: bool,
+    ascii_flag: bool,
+    bar_flag: bool,
+    bar_width: usize,
+    full_in_flag: bool,
+    full_horizon: Duration,
+    theme: Theme,
+    html_usage_style_flag: bool,
+    overlay_layers_flag: bool,
+    total_flag: bool,
+    sync_flag: bool,
+    no_header_flag: bool,
+    verbose_flag: bool,
+    quiet_flag: bool,
+    target_only_flag: bool,
+    problems_only_flag: bool,
+    wide_flag: bool,
+    compat: Compat,
+    include_types: Vec<String>,
+    exclude_types: HashSet<String>,
+    pseudo_types: HashSet<String>,
+    exclude_mount_points: Vec<PathBuf>,
+    include_mount_point_globs: Vec<String>,
+    source_regex: Option<Regex>,
+    top_count: Option<usize>,
+    over_percent: Option<u64>,
+    alert_percent: Option<u64>,
+    alert_command: Option<String>,
+    warning_percent: Option<u64>,
+    critical_percent: Option<u64>,
+    log_target: Option<LogTarget>,
+    sort_field: Option<Field>,
+    summary_key: Option<SummaryKey>,
+    reverse_flag: bool,
+    hierarchy_flag: bool,
+    tree_flag: bool,
+    bind_mount_mode: BindMountMode,
+    stdin_flag: bool,
+    files_from: Option<(String, u8)>,
+    mtab: PathBuf,
+    mountinfo: Option<PathBuf>,
+    root: Option<PathBuf>,
+    namespace: Option<PathBuf>,
+    output_fields: Option<Vec<Field>>,
+    output_format: OutputFormat,
+    truncated_fields: Vec<Field>,
+    wrap_flag: bool,
+    pager_flag: bool,
+    separator: Option<String>,
+    statsd_addr: Option<String>,
+    watch_interval: Option<Duration>,
+    #[cfg(feature = "otlp")]
+    otlp_endpoint: Option<String>,
+    #[cfg(feature = "sqlite")]
+    record_path: Option<PathBuf>,
+    template: Vec<TemplateItem>,
 }
 
-struct FormatEntry
+impl Options
+{
+    /// Returns the options of the default output of this program.
+    fn new() -> Options
+    {
+        Options {
+            all_flag: false,
+            local_flag: false,
+            kilo_flag: false,
+            size_unit: None,
+            precision: None,
+            rounding: None,
+            group_digits_flag: false,
+            numeric_locale: NumericLocale::from_env(),
+            disk_ids: None,
+            inode_flag: false,
+            type_flag: false,
+            options_flag: false,
+            mark_read_only_flag: false,
+            color_flag: false,
+            ascii_flag: !is_utf8_locale(),
+            bar_flag: false,
+            bar_width: DEFAULT_BAR_WIDTH,
+            full_in_flag: false,
+            full_horizon: DEFAULT_FULL_HORIZON,
+            theme: Theme::new(),
+            html_usage_style_flag: false,
+            overlay_layers_flag: false,
+            total_flag: false,
+            sync_flag: false,
+            no_header_flag: false,
+            verbose_flag: false,
+            quiet_flag: false,
+            target_only_flag: false,
+            problems_only_flag: false,
+            wide_flag: false,
+            compat: Compat::Posix,
+            include_types: Vec::new(),
+            exclude_types: HashSet::new(),
+            pseudo_types: PSEUDO_FILE_SYSTEM_TYPES.iter().map(|name| String::from(*name)).collect(),
+            exclude_mount_points: Vec::new(),
+            include_mount_point_globs: Vec::new(),
+            source_regex: None,
+            top_count: None,
+            over_percent: None,
+            alert_percent: None,
+            alert_command: None,
+            warning_percent: None,
+            critical_percent: None,
+            log_target: None,
+            sort_field: None,
+            summary_key: None,
+            reverse_flag: false,
+            hierarchy_flag: false,
+            tree_flag: false,
+            bind_mount_mode: BindMountMode::Show,
+            stdin_flag: false,
+            files_from: None,
+            mtab: PathBuf::from(PROC_MOUNTS),
+            mountinfo: Some(PathBuf::from("/proc/self/mountinfo")),
+            root: None,
+            namespace: None,
+            output_fields: None,
+            output_format: OutputFormat::Table,
+            truncated_fields: vec![Field::Source],
+            wrap_flag: false,
+            pager_flag: true,
+            separator: None,
+            statsd_addr: None,
+            watch_interval: None,
+            #[cfg(feature = "otlp")]
+            otlp_endpoint: None,
+            #[cfg(feature = "sqlite")]
+            record_path: None,
+            template: Vec::new(),
+        }
+    }
+}
+
+const OPTIONS: &[OptionDesc] = &[
+    OptionDesc { short_name: Some('0'), long_name: Some("print0"), arg_name: None, is_arg_optional: false, description: "separate fields by NUL and rows by two NULs", },
+    OptionDesc { short_name: Some('a'), long_name: Some("all"), arg_name: None, is_arg_optional: false, description: "include filesystems that have zero blocks", },
+    OptionDesc { short_name: None, long_name: Some("add-pseudo-fs"), arg_name: Some("TYPE"), is_arg_optional: false, description: "treat filesystems of type TYPE as pseudo filesystems", },
+    OptionDesc { short_name: None, long_name: Some("ascii"), arg_name: None, is_arg_optional: false, description: "use only ASCII characters in ellipses and tree lines", },
+    OptionDesc { short_name: Some('B'), long_name: Some("block-size"), arg_name: Some("SIZE"), is_arg_optional: false, description: "scale sizes by SIZE, for example 1M or 4KiB", },
+    OptionDesc { short_name: Some('b'), long_name: Some("bytes"), arg_name: None, is_arg_optional: false, description: "print exact sizes in bytes", },
+    OptionDesc { short_name: None, long_name: Some("bar"), arg_name: Some("WIDTH"), is_arg_optional: true, description: "print usage bars of WIDTH characters (20 by default)", },
+    OptionDesc { short_name: None, long_name: Some("bind-mounts"), arg_name: Some("MODE"), is_arg_optional: false, description: "handle bind mounts according to MODE (show, hide or annotate)", },
+    OptionDesc { short_name: Some('c'), long_name: Some("critical"), arg_name: Some("PERCENT"), is_arg_optional: false, description: "report a critical state for capacities of at least PERCENT", },
+    OptionDesc { short_name: None, long_name: Some("check"), arg_name: None, is_arg_optional: false, description: "check capacities against thresholds as a Nagios plugin", },
+    OptionDesc { short_name: None, long_name: Some("color"), arg_name: Some("WHEN"), is_arg_optional: true, description: "color capacities and pseudo filesystems (auto, always or never)", },
+    OptionDesc { short_name: None, long_name: Some("compat"), arg_name: Some("MODE"), is_arg_optional: false, description: "use the output format of MODE (gnu, posix or busybox)", },
+    OptionDesc { short_name: None, long_name: Some("csv"), arg_name: None, is_arg_optional: false, description: "print filesystems as comma-separated values", },
+    OptionDesc { short_name: None, long_name: Some("exclude-mountpoint"), arg_name: Some("PREFIX"), is_arg_optional: false, description: "exclude filesystems mounted under PREFIX", },
+    OptionDesc { short_name: None, long_name: Some("exec-alert"), arg_name: Some("CMD"), is_arg_optional: false, description: "run the shell command CMD for each filesystem over the threshold of --on-over", },
+    OptionDesc { short_name: None, long_name: Some("files-from"), arg_name: Some("FILE"), is_arg_optional: false, description: "read paths from FILE, one per line", },
+    OptionDesc { short_name: None, long_name: Some("files0-from"), arg_name: Some("FILE"), is_arg_optional: false, description: "read NUL-terminated paths from FILE", },
+    OptionDesc { short_name: None, long_name: Some("format"), arg_name: Some("TEMPLATE"), is_arg_optional: false, description: "print filesystems according to TEMPLATE, for example '{target} {avail:h}'", },
+    OptionDesc { short_name: None, long_name: Some("full-in"), arg_name: Some("HORIZON"), is_arg_optional: true, description: "print estimated time until full and flag filesystems full within HORIZON (7d by default)", },
+    OptionDesc { short_name: None, long_name: Some("group-digits"), arg_name: None, is_arg_optional: false, description: "separate groups of thousands in numbers", },
+    OptionDesc { short_name: Some('H'), long_name: Some("si"), arg_name: None, is_arg_optional: false, description: "print sizes in powers of 1000, for example 1.1G", },
+    OptionDesc { short_name: Some('h'), long_name: Some("human-readable"), arg_name: None, is_arg_optional: false, description: "print sizes in powers of 1024, for example 1.0G", },
+    OptionDesc { short_name: None, long_name: Some("hierarchy"), arg_name: None, is_arg_optional: false, description: "list filesystems directly under their parent filesystems", },
+    OptionDesc { short_name: None, long_name: Some("html"), arg_name: None, is_arg_optional: false, description: "print filesystems as an HTML document with a table", },
+    OptionDesc { short_name: None, long_name: Some("html-usage-style"), arg_name: None, is_arg_optional: false, description: "show capacities in HTML tables as bars", },
+    OptionDesc { short_name: Some('i'), long_name: Some("inodes"), arg_name: None, is_arg_optional: false, description: "print inode information instead of block usage", },
+    OptionDesc { short_name: None, long_name: Some("include"), arg_name: Some("GLOB"), is_arg_optional: false, description: "show only filesystems mounted on paths matching GLOB", },
+    OptionDesc { short_name: None, long_name: Some("influx"), arg_name: None, is_arg_optional: false, description: "print filesystems in the InfluxDB line protocol as the disk input of Telegraf", },
+    OptionDesc { short_name: None, long_name: Some("json"), arg_name: None, is_arg_optional: false, description: "print filesystems as a JSON array of objects", },
+    OptionDesc { short_name: None, long_name: Some("jsonl"), arg_name: None, is_arg_optional: false, description: "print filesystems as JSON objects, one per line", },
+    OptionDesc { short_name: Some('k'), long_name: None, arg_name: None, is_arg_optional: false, description: "use 1024-byte blocks instead of 512-byte blocks", },
+    OptionDesc { short_name: Some('l'), long_name: Some("local"), arg_name: None, is_arg_optional: false, description: "show only local filesystems", },
+    OptionDesc { short_name: None, long_name: Some("log"), arg_name: Some("TARGET"), is_arg_optional: false, description: "log filesystems crossing thresholds of -w and -c to TARGET (syslog or journal)", },
+    OptionDesc { short_name: None, long_name: Some("mark-read-only"), arg_name: None, is_arg_optional: false, description: "mark mount points of read-only filesystems with (ro)", },
+    OptionDesc { short_name: None, long_name: Some("markdown"), arg_name: None, is_arg_optional: false, description: "print filesystems as a Markdown table", },
+    OptionDesc { short_name: None, long_name: Some("mtab"), arg_name: Some("FILE"), is_arg_optional: false, description: "read mount entries from FILE instead of /proc/mounts", },
+    OptionDesc { short_name: None, long_name: Some("namespace"), arg_name: Some("FILE"), is_arg_optional: false, description: "enter the mount namespace of FILE, for example /proc/PID/ns/mnt", },
+    OptionDesc { short_name: None, long_name: Some("no-header"), arg_name: None, is_arg_optional: false, description: "don't print the header row", },
+    OptionDesc { short_name: None, long_name: Some("no-pager"), arg_name: None, is_arg_optional: false, description: "don't pipe the output to a pager", },
+    OptionDesc { short_name: None, long_name: Some("no-truncate"), arg_name: None, is_arg_optional: false, description: "don't truncate fields to fit in the terminal width", },
+    OptionDesc { short_name: Some('o'), long_name: Some("print-options"), arg_name: None, is_arg_optional: false, description: "print mount options", },
+    OptionDesc { short_name: None, long_name: Some("on-over"), arg_name: Some("PERCENT"), is_arg_optional: false, description: "run the command of --exec-alert for filesystems with capacity of at least PERCENT", },
+    #[cfg(feature = "otlp")]
+    OptionDesc { short_name: None, long_name: Some("otlp"), arg_name: Some("URL"), is_arg_optional: false, description: "push usage of filesystems as gauges to the OTLP/HTTP endpoint at URL", },
+    OptionDesc { short_name: None, long_name: Some("output"), arg_name: Some("FIELDS"), is_arg_optional: false, description: "use the output format defined by comma-separated FIELDS", },
+    OptionDesc { short_name: None, long_name: Some("over"), arg_name: Some("PERCENT"), is_arg_optional: false, description: "show only filesystems with capacity of at least PERCENT", },
+    OptionDesc { short_name: None, long_name: Some("overlay-layers"), arg_name: None, is_arg_optional: false, description: "show filesystems of layers of overlay filesystems", },
+    OptionDesc { short_name: Some('P'), long_name: Some("portability"), arg_name: None, is_arg_optional: false, description: "use the POSIX output format", },
+    OptionDesc { short_name: None, long_name: Some("pid"), arg_name: Some("PID"), is_arg_optional: false, description: "show filesystems as seen by the process PID", },
+    OptionDesc { short_name: None, long_name: Some("precision"), arg_name: Some("N"), is_arg_optional: false, description: "print human-readable sizes with N decimal digits", },
+    OptionDesc { short_name: None, long_name: Some("problems-only"), arg_name: None, is_arg_optional: false, description: "print only filesystems reaching -w or -c (90% by default) or full soon", },
+    OptionDesc { short_name: None, long_name: Some("prometheus"), arg_name: None, is_arg_optional: false, description: "print filesystem metrics in the Prometheus text format of node_exporter", },
+    OptionDesc { short_name: None, long_name: Some("pseudo-fs"), arg_name: Some("TYPES"), is_arg_optional: false, description: "set comma-separated types of pseudo filesystems", },
+    OptionDesc { short_name: Some('q'), long_name: Some("quiet"), arg_name: None, is_arg_optional: false, description: "don't print errors of getting filesystem usage", },
+    #[cfg(feature = "sqlite")]
+    OptionDesc { short_name: None, long_name: Some("record"), arg_name: Some("DB"), is_arg_optional: true, description: "append usage of filesystems with the current time to the SQLite database DB or the default database", },
+    OptionDesc { short_name: None, long_name: Some("records"), arg_name: None, is_arg_optional: false, description: "print each filesystem as lines of fields and values", },
+    OptionDesc { short_name: None, long_name: Some("reverse"), arg_name: None, is_arg_optional: false, description: "reverse the order of filesystems", },
+    OptionDesc { short_name: None, long_name: Some("root"), arg_name: Some("DIR"), is_arg_optional: false, description: "show filesystems of the system mounted on DIR", },
+    OptionDesc { short_name: None, long_name: Some("rounding"), arg_name: Some("MODE"), is_arg_optional: false, description: "round sizes and percentages according to MODE (up, down or nearest)", },
+    OptionDesc { short_name: None, long_name: Some("separator"), arg_name: Some("STR"), is_arg_optional: false, description: "separate columns by STR without padding, for example '|' or '\\t'", },
+    OptionDesc { short_name: None, long_name: Some("shell"), arg_name: None, is_arg_optional: false, description: "print filesystems as shell variable assignments", },
+    OptionDesc { short_name: None, long_name: Some("sort"), arg_name: Some("FIELD"), is_arg_optional: false, description: "sort filesystems by FIELD, for example size or pcent", },
+    OptionDesc { short_name: None, long_name: Some("source-regex"), arg_name: Some("REGEX"), is_arg_optional: false, description: "show only filesystems which have sources matching REGEX", },
+    OptionDesc { short_name: None, long_name: Some("statsd"), arg_name: Some("HOST:PORT"), is_arg_optional: false, description: "send usage of filesystems as gauges to the StatsD server at HOST:PORT", },
+    OptionDesc { short_name: None, long_name: Some("stdin"), arg_name: None, is_arg_optional: false, description: "read paths from the standard input, one per line", },
+    OptionDesc { short_name: None, long_name: Some("summary"), arg_name: Some("KEY"), is_arg_optional: false, description: "print sums of usage of filesystems grouped by KEY (fstype)", },
+    OptionDesc { short_name: None, long_name: Some("sync"), arg_name: None, is_arg_optional: false, description: "synchronize filesystems before getting usage", },
+    OptionDesc { short_name: Some('T'), long_name: Some("print-type"), arg_name: None, is_arg_optional: false, description: "print filesystem types", },
+    OptionDesc { short_name: Some('t'), long_name: Some("type"), arg_name: Some("TYPE"), is_arg_optional: false, description: "show only filesystems of type TYPE", },
+    OptionDesc { short_name: None, long_name: Some("target-only"), arg_name: None, is_arg_optional: false, description: "print only mount points of filesystems of FILEs", },
+    OptionDesc { short_name: None, long_name: Some("top"), arg_name: Some("N"), is_arg_optional: false, description: "show only N filesystems with the highest capacity", },
+    OptionDesc { short_name: None, long_name: Some("total"), arg_name: None, is_arg_optional: false, description: "print a grand total", },
+    OptionDesc { short_name: None, long_name: Some("tree"), arg_name: None, is_arg_optional: false, description: "show mount points as a tree", },
+    OptionDesc { short_name: None, long_name: Some("truncate"), arg_name: Some("FIELDS"), is_arg_optional: false, description: "truncate FIELDS to fit in the terminal width (source by default)", },
+    OptionDesc { short_name: None, long_name: Some("tsv"), arg_name: None, is_arg_optional: false, description: "print filesystems as tab-separated values", },
+    OptionDesc { short_name: Some('v'), long_name: Some("verbose"), arg_name: None, is_arg_optional: false, description: "print raw statvfs values to the standard error", },
+    OptionDesc { short_name: Some('w'), long_name: Some("warning"), arg_name: Some("PERCENT"), is_arg_optional: false, description: "report a warning state for capacities of at least PERCENT", },
+    OptionDesc { short_name: None, long_name: Some("watch"), arg_name: Some("SECONDS"), is_arg_optional: true, description: "print filesystems every SECONDS seconds (2 by default)", },
+    OptionDesc { short_name: None, long_name: Some("wide"), arg_name: None, is_arg_optional: false, description: "print block usage and inode information in one table", },
+    OptionDesc { short_name: None, long_name: Some("wrap"), arg_name: None, is_arg_optional: false, description: "print long sources in their own lines as GNU df", },
+    OptionDesc { short_name: Some('x'), long_name: Some("exclude-type"), arg_name: Some("TYPE"), is_arg_optional: false, description: "exclude filesystems of type TYPE", },
+    #[cfg(feature = "xml")]
+    OptionDesc { short_name: None, long_name: Some("xml"), arg_name: None, is_arg_optional: false, description: "print filesystems as XML elements", },
+    OptionDesc { short_name: None, long_name: Some("yaml"), arg_name: None, is_arg_optional: false, description: "print filesystems as a YAML sequence of mappings", },
+    OptionDesc { short_name: None, long_name: Some("zabbix-lld"), arg_name: None, is_arg_optional: false, description: "print filesystems as a low-level discovery document of Zabbix", },
+    OptionDesc { short_name: None, long_name: Some("zabbix-value"), arg_name: Some("MODE"), is_arg_optional: false, description: "print values of Zabbix items of MODE (total, free, used, pfree or pused)", },
+    OptionDesc { short_name: None, long_name: Some("help"), arg_name: None, is_arg_optional: false, description: "display this help and exit", },
+    OptionDesc { short_name: None, long_name: Some("version"), arg_name: None, is_arg_optional: false, description: "output version information and exit", },
+];
+
+const PROC_MOUNTS: &str = "/proc/mounts";
+
+const MAX_PRECISION: usize = 9;
+
+const DEFAULT_BAR_WIDTH: usize = 20;
+
+/// The horizon of flagging filesystems which are estimated to be full soon.
+const DEFAULT_FULL_HORIZON: Duration = Duration::from_secs(7 * 86400);
+
+/// The warning threshold of `--problems-only` if no threshold is given.
+const DEFAULT_PROBLEM_PERCENT: u64 = 90;
+
+const MAX_BAR_WIDTH: usize = 1000;
+
+const ELLIPSIS: &str = "…";
+const ASCII_ELLIPSIS: &str = "...";
+
+/// The minimal width of a truncated column.
+const MIN_TRUNCATED_WIDTH: usize = 8;
+
+/// The width of the source column if long sources are printed in their own lines.
+const SOURCE_WRAP_WIDTH: usize = 20;
+
+const STATVFS_FLAGS: &[(libc::c_ulong, &str)] = &[
+    (libc::ST_NOSUID, "nosuid"),
+    (libc::ST_NODEV, "nodev"),
+    (libc::ST_NOEXEC, "noexec"),
+    (libc::ST_SYNCHRONOUS, "sync"),
+    (libc::ST_MANDLOCK, "mand"),
+    (libc::ST_NOATIME, "noatime"),
+    (libc::ST_NODIRATIME, "nodiratime"),
+    (libc::ST_RELATIME, "relatime"),
+];
+
+/// Types of pseudo filesystems which are hidden unless all filesystems are shown.
+const PSEUDO_FILE_SYSTEM_TYPES: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "rpc_pipefs",
+    "securityfs",
+    "selinuxfs",
+    "sysfs",
+    "tracefs",
+];
+
+const REMOTE_FILE_SYSTEM_TYPES: &[&str] = &[
+    "9p",
+    "afs",
+    "ceph",
+    "cifs",
+    "coda",
+    "davfs",
+    "fuse.davfs2",
+    "fuse.glusterfs",
+    "fuse.rclone",
+    "fuse.s3fs",
+    "fuse.sshfs",
+    "glusterfs",
+    "lustre",
+    "ncpfs",
+    "nfs",
+    "nfs4",
+    "smb3",
+    "smbfs",
+    "sshfs",
+];
+
+struct UsageEntry
 {
+    file: Option<String>,
     file_system: String,
-    total: String,
-    used: String,
-    available: String,
-    capacity: String,
+    file_system_type: String,
     mount_point: String,
+    mount_options: String,
+    flags: Option<libc::c_ulong>,
+    fsid: Option<u64>,
+    name_max: Option<u64>,
+    device: Option<(u32, u32)>,
+    uuid: Option<String>,
+    label: Option<String>,
+    backing_file: Option<String>,
+    upper_dir: Option<String>,
+    lower_dirs: Option<String>,
+    server: Option<(String, String)>,
+    propagation: Option<String>,
+    mount_id: Option<u64>,
+    parent_id: Option<u64>,
+    subvol: Option<String>,
+    subvol_id: Option<String>,
+    is_bind_mount: bool,
+    is_overlay_layer: bool,
+    tree_prefix: String,
+    total_bytes: u64,
+    used_bytes: u64,
+    available_bytes: i64,
+    reserved_bytes: u64,
+    total_inodes: u64,
+    used_inodes: u64,
+    available_inodes: u64,
+    /// The estimated number of seconds until the filesystem is full.
+    full_in: Option<u64>,
+}
+
+impl UsageEntry
+{
+    /// Returns `true` if the filesystem is estimated to be full within the horizon.
+    fn is_full_soon(&self, opts: &Options) -> bool
+    {
+        self.full_in.map(|full_in| full_in <= opts.full_horizon.as_secs()).unwrap_or(false)
+    }
+
+    /// Returns `true` if the filesystem reaches a threshold or is estimated to be full soon.
+    fn is_problem(&self, opts: &Options) -> bool
+    {
+        threshold_state(self.capacity(opts), opts) != PluginState::Ok || self.is_full_soon(opts)
+    }
+
+    fn is_read_only(&self) -> bool
+    {
+        self.flags.map(|flags| flags & libc::ST_RDONLY != 0).unwrap_or(false)
+    }
+
+    fn capacity(&self, opts: &Options) -> u64
+    {
+        self.capacity_percent(opts).unwrap_or(0)
+    }
+
+    /// Returns the capacity of blocks or the capacity of inodes if inode information is printed.
+    fn capacity_percent(&self, opts: &Options) -> Option<u64>
+    {
+        if opts.inode_flag {
+            capacity_percent(self.used_inodes, i128::from(self.available_inodes), percent_rounding(opts))
+        } else {
+            capacity_percent(self.used_bytes, i128::from(self.available_bytes), percent_rounding(opts))
+        }
+    }
 }
 
-struct FormatMaxLengths
+struct FormatEntry
 {
-    max_file_system_len: usize,
-    max_total_len: usize,
-    max_used_len: usize,
-    max_available_len: usize,
-    max_capacity_len: usize,
-    max_mount_point_len: usize,
+    values: Vec<String>,
+    /// SGR parameters of the values if the values are colored.
+    styles: Vec<Option<String>>,
 }
 
-#[allow(dead_code)]
 struct StatVFS
 {
     bsize: usize,
@@ -71,33 +591,115 @@ struct StatVFS
     namemax: usize,
 }
 
+fn statvfs_buf_to_statvfs(statvfs_buf: &libc::statvfs) -> StatVFS
+{
+    StatVFS {
+        bsize: statvfs_buf.f_bsize as usize,
+        frsize: statvfs_buf.f_frsize as usize,
+        blocks: statvfs_buf.f_blocks,
+        bfree: statvfs_buf.f_bfree,
+        bavail: statvfs_buf.f_bavail,
+        files: statvfs_buf.f_files,
+        ffree: statvfs_buf.f_ffree,
+        favail: statvfs_buf.f_favail,
+        fsid: statvfs_buf.f_fsid as usize,
+        flag: statvfs_buf.f_flag as usize,
+        namemax: statvfs_buf.f_namemax as usize,
+    }
+}
+
 fn statvfs<P: AsRef<Path>>(path: P) -> Result<StatVFS>
 {
     let path_cstring = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
     let mut statvfs_buf: MaybeUninit<libc::statvfs> = MaybeUninit::uninit();
-    let res = unsafe { libc::statvfs(path_cstring.as_ptr(), statvfs_buf.assume_init_mut() as *mut libc::statvfs) };
+    let res = unsafe { libc::statvfs(path_cstring.as_ptr(), statvfs_buf.as_mut_ptr()) };
+    if res != -1 {
+        Ok(statvfs_buf_to_statvfs(unsafe { statvfs_buf.assume_init_ref() }))
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+fn fstatvfs_path<P: AsRef<Path>>(path: P) -> Result<StatVFS>
+{
+    let path_cstring = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let fd = unsafe { libc::open(path_cstring.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+    if fd == -1 {
+        return Err(Error::last_os_error());
+    }
+    let mut statvfs_buf: MaybeUninit<libc::statvfs> = MaybeUninit::uninit();
+    let res = unsafe { libc::fstatvfs(fd, statvfs_buf.as_mut_ptr()) };
+    let res_err = Error::last_os_error();
+    unsafe { libc::close(fd) };
+    if res != -1 {
+        Ok(statvfs_buf_to_statvfs(unsafe { statvfs_buf.assume_init_ref() }))
+    } else {
+        Err(res_err)
+    }
+}
+
+fn fnmatch<P: AsRef<Path>>(pattern: &str, path: P) -> bool
+{
+    let pattern_cstring = CString::new(pattern).unwrap();
+    let path_cstring = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    unsafe { libc::fnmatch(pattern_cstring.as_ptr(), path_cstring.as_ptr(), libc::FNM_PATHNAME) == 0 }
+}
+
+fn syncfs<P: AsRef<Path>>(path: P) -> Result<()>
+{
+    let file = fs::File::open(path)?;
+    let res = unsafe { libc::syncfs(file.as_raw_fd()) };
+    if res != -1 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+/// Returns a path for system calls, that is the path under the root directory if the root
+/// directory is specified.
+fn rooted_path<P: AsRef<Path>>(path: P, opts: &Options) -> PathBuf
+{
+    match &opts.root {
+        Some(root) => root.join(path.as_ref().strip_prefix("/").unwrap_or(path.as_ref())),
+        None       => path.as_ref().to_path_buf(),
+    }
+}
+
+/// Converts a path under the root directory to a path relative to the root directory.
+fn unrooted_path(path: PathBuf, opts: &Options) -> PathBuf
+{
+    match &opts.root {
+        Some(root) => {
+            match path.strip_prefix(root) {
+                Ok(rel_path) => Path::new("/").join(rel_path),
+                Err(_)       => path,
+            }
+        },
+        None => path,
+    }
+}
+
+fn setns_mount<P: AsRef<Path>>(path: P) -> Result<()>
+{
+    let file = fs::File::open(path)?;
+    let res = unsafe { libc::setns(file.as_raw_fd(), libc::CLONE_NEWNS) };
     if res != -1 {
-        Ok(StatVFS {
-                bsize: unsafe { statvfs_buf.assume_init_ref() }.f_bsize as usize,
-                frsize: unsafe { statvfs_buf.assume_init_ref() }.f_frsize as usize,
-                blocks: unsafe { statvfs_buf.assume_init_ref() }.f_blocks as u64,
-                bfree: unsafe { statvfs_buf.assume_init_ref() }.f_bfree as u64,
-                bavail: unsafe { statvfs_buf.assume_init_ref() }.f_bavail as u64,
-                files: unsafe { statvfs_buf.assume_init_ref() }.f_files as u64,
-                ffree: unsafe { statvfs_buf.assume_init_ref() }.f_ffree as u64,
-                favail: unsafe { statvfs_buf.assume_init_ref() }.f_favail as u64,
-                fsid: unsafe { statvfs_buf.assume_init_ref() }.f_fsid as usize,
-                flag: unsafe { statvfs_buf.assume_init_ref() }.f_flag as usize,
-                namemax: unsafe { statvfs_buf.assume_init_ref() }.f_namemax as usize,
-        })
+        Ok(())
     } else {
         Err(Error::last_os_error())
     }
 }
 
-fn get_mounts() -> result::Result<Vec<MountEntry>, mnt::ParseError>
+fn open_mounts<P: AsRef<Path>>(mtab: P) -> result::Result<MountIter<BufReader<fs::File>>, mnt::ParseError>
 {
-    let iter = MountIter::new_from_proc()?;
+    let file = fs::File::open(mtab)?;
+    Ok(MountIter::new(BufReader::new(file)))
+}
+
+fn get_mounts<P: AsRef<Path>>(mtab: P) -> result::Result<Vec<MountEntry>, mnt::ParseError>
+{
+    let iter = open_mounts(mtab)?;
     let mut entries: Vec<MountEntry> = Vec::new();
     for entry in iter {
         match entry {
@@ -108,19 +710,41 @@ fn get_mounts() -> result::Result<Vec<MountEntry>, mnt::ParseError>
     Ok(entries)
 }
 
-fn find_mount<P: AsRef<Path>>(path: P) -> result::Result<Option<MountEntry>, mnt::ParseError>
+fn dedup_mounts(mount_entries: Vec<MountEntry>, opts: &Options) -> Vec<MountEntry>
+{
+    let mut entries: Vec<MountEntry> = Vec::new();
+    let mut dev_indices: HashMap<u64, usize> = HashMap::new();
+    for entry in mount_entries {
+        match fs::metadata(rooted_path(entry.file.as_path(), opts)) {
+            Ok(metadata) => {
+                match dev_indices.get(&metadata.dev()) {
+                    Some(i) => {
+                        // Prefers the shortest mount point, for example a filesystem root to its bind mounts.
+                        if entry.file.as_os_str().len() < entries[*i].file.as_os_str().len() {
+                            entries[*i] = entry;
+                        }
+                    },
+                    None => {
+                        dev_indices.insert(metadata.dev(), entries.len());
+                        entries.push(entry);
+                    },
+                }
+            },
+            Err(_) => entries.push(entry),
+        }
+    }
+    entries
+}
+
+fn find_mount<P: AsRef<Path>, Q: AsRef<Path>>(path: P, mtab: Q) -> result::Result<Option<MountEntry>, mnt::ParseError>
 {
-    let iter = MountIter::new_from_proc()?;
+    let iter = open_mounts(mtab)?;
     let mut mount_entry: Option<MountEntry> = None;
     let mut file_len = 0;
     for entry in iter {
         match entry {
             Ok(entry) => {
-                let spec = path.as_ref().to_string_lossy().into_owned();
-                if entry.spec.starts_with("/") && entry.spec == spec {
-                    mount_entry = Some(entry.clone());
-                    file_len = usize::MAX;
-                } else if path.as_ref().starts_with(&entry.file) {
+                if path.as_ref().starts_with(&entry.file) {
                     let tmp_file_len = entry.file.as_path().to_string_lossy().len();
                     if tmp_file_len > file_len {
                         mount_entry = Some(entry.clone());
@@ -134,144 +758,1550 @@ fn find_mount<P: AsRef<Path>>(path: P) -> result::Result<Option<MountEntry>, mnt
     Ok(mount_entry)
 }
 
-fn header_format_entry(opts: &Options) -> FormatEntry
+fn is_mounted_device(entry: &MountEntry, rdev: u64, mountinfo_entries: &[MountInfoEntry]) -> bool
 {
-    let total = if opts.kilo_flag { String::from("1024-blocks") } else { String::from("512-blocks") };
-    FormatEntry {
-        file_system: String::from("Filesystem"),
-        total,
-        used: String::from("Used"),
-        available: String::from("Available"),
-        capacity: String::from("Capacity"),
-        mount_point: String::from("Mounted on"),
+    match mount_entry_mountinfo_entry(entry, mountinfo_entries) {
+        Some(mountinfo_entry) => mountinfo_entry.major == libc::major(rdev) && mountinfo_entry.minor == libc::minor(rdev),
+        None => {
+            match fs::metadata(entry.spec.as_str()) {
+                Ok(metadata) => metadata.file_type().is_block_device() && metadata.rdev() == rdev,
+                Err(_)       => false,
+            }
+        },
     }
 }
 
-fn mount_entry_to_format_entry(mount_entry: &MountEntry, opts: &Options, is_vfs: bool) -> Option<Option<FormatEntry>>
+fn find_device_mount<P: AsRef<Path>>(rdev: u64, mtab: P, mountinfo_entries: &[MountInfoEntry]) -> result::Result<Option<MountEntry>, mnt::ParseError>
 {
-    match statvfs(mount_entry.file.as_path()) {
-        Ok(statvfs) => {
-            let unit_size = if opts.kilo_flag { 1024 } else { 512 };
-            let total_blocks = statvfs.blocks;
-            if total_blocks != 0 || is_vfs {
-                let used_blocks = statvfs.blocks - statvfs.bfree;
-                let available_blocks = statvfs.bavail;
-                let total_blocks2 = used_blocks + available_blocks;
-                let capacity = if total_blocks2 != 0 {
-                    format!("{}%", (used_blocks * 100 + total_blocks2 - 1) / total_blocks2)
-                } else {
-                    String::from("0%")
-                };
-                let file_system = mount_entry.spec.clone();
-                let total = format!("{}", (total_blocks * statvfs.frsize as u64 + unit_size - 1) / unit_size);
-                let used = format!("{}", (used_blocks * statvfs.frsize as u64 + unit_size - 1) / unit_size);
-                let available = format!("{}", (available_blocks * statvfs.frsize as u64) / unit_size);
-                let mount_point = format!("{}", mount_entry.file.as_path().to_string_lossy());
-                Some(Some(FormatEntry {
-                        file_system,
-                        total,
-                        used,
-                        available,
-                        capacity,
-                        mount_point,
-                }))
+    let iter = open_mounts(mtab)?;
+    let mut mount_entry: Option<MountEntry> = None;
+    for entry in iter {
+        match entry {
+            Ok(entry) => {
+                if is_mounted_device(&entry, rdev, mountinfo_entries) {
+                    // Prefers a mount of the filesystem root to bind mounts and then the shortest mount point.
+                    let is_better = match &mount_entry {
+                        Some(old_entry) => {
+                            let key = (is_bind_mount_entry(&entry, mountinfo_entries), entry.file.as_os_str().len());
+                            let old_key = (is_bind_mount_entry(old_entry, mountinfo_entries), old_entry.file.as_os_str().len());
+                            key < old_key
+                        },
+                        None => true,
+                    };
+                    if is_better {
+                        mount_entry = Some(entry);
+                    }
+                }
+            },
+            Err(err)  => return Err(err),
+        }
+    }
+    Ok(mount_entry)
+}
+
+fn is_remote_mount_entry(mount_entry: &MountEntry) -> bool
+{
+    if REMOTE_FILE_SYSTEM_TYPES.contains(&mount_entry.vfstype.as_str()) {
+        return true;
+    }
+    // Fuse-based remote filesystems usually have a source in the form "host:path".
+    mount_entry.vfstype.starts_with("fuse") && !mount_entry.spec.starts_with('/') && mount_entry.spec.contains(':')
+}
+
+/// Returns the server host and the export path of a remote filesystem. Sources in the forms
+/// `host:/path`, `[address]:/path`, `user@host:path` and `//host/share` are recognized.
+fn remote_server(mount_entry: &MountEntry) -> Option<(String, String)>
+{
+    if !is_remote_mount_entry(mount_entry) {
+        return None;
+    }
+    let spec = mount_entry.spec.as_str();
+    if let Some(rest) = spec.strip_prefix("//") {
+        let (host, share) = rest.split_once('/').unwrap_or((rest, ""));
+        return Some((String::from(host), format!("/{}", share)));
+    }
+    let (host, path) = match spec.strip_prefix('[') {
+        Some(rest) => rest.split_once("]:")?,
+        None       => spec.split_once(':')?,
+    };
+    let host = host.rsplit_once('@').map(|pair| pair.1).unwrap_or(host);
+    Some((String::from(host), String::from(path)))
+}
+
+fn is_selected_mount_entry(mount_entry: &MountEntry, opts: &Options) -> bool
+{
+    (opts.include_types.is_empty() || opts.include_types.contains(&mount_entry.vfstype)) &&
+    !opts.exclude_types.contains(&mount_entry.vfstype) &&
+    !(opts.local_flag && is_remote_mount_entry(mount_entry)) &&
+    !opts.exclude_mount_points.iter().any(|prefix| mount_entry.file.starts_with(prefix)) &&
+    (opts.include_mount_point_globs.is_empty() || opts.include_mount_point_globs.iter().any(|glob| fnmatch(glob, mount_entry.file.as_path()))) &&
+    opts.source_regex.as_ref().map(|regex| regex.is_match(mount_entry.spec.as_str())).unwrap_or(true)
+}
+
+fn selected_fields(opts: &Options) -> Vec<Field>
+{
+    match &opts.output_fields {
+        Some(fields) => fields.clone(),
+        None if opts.output_format == OutputFormat::Template => template_fields(opts.template.as_slice()),
+        None if opts.output_format.is_structured() => {
+            // Structured formats have all numbers by default because they don't have to fit in
+            // a terminal.
+            let mut fields: Vec<Field> = vec![Field::Source, Field::FsType];
+            fields.extend_from_slice(&[Field::Size, Field::Used, Field::Avail, Field::Pcent]);
+            fields.extend_from_slice(&[Field::ITotal, Field::IUsed, Field::IAvail, Field::IPcent]);
+            if opts.full_in_flag {
+                fields.push(Field::FullIn);
+            }
+            fields.push(Field::Target);
+            if opts.options_flag {
+                fields.push(Field::Options);
+            }
+            fields
+        },
+        None => {
+            let mut fields: Vec<Field> = vec![Field::Source];
+            if opts.type_flag {
+                fields.push(Field::FsType);
+            }
+            if opts.wide_flag {
+                fields.extend_from_slice(&[Field::Size, Field::Used, Field::Avail, Field::Pcent]);
+                fields.extend_from_slice(&[Field::ITotal, Field::IUsed, Field::IAvail, Field::IPcent]);
+            } else if opts.inode_flag {
+                fields.extend_from_slice(&[Field::ITotal, Field::IUsed, Field::IAvail, Field::IPcent]);
             } else {
-                Some(None)
+                fields.extend_from_slice(&[Field::Size, Field::Used, Field::Avail, Field::Pcent]);
+            }
+            if opts.bar_flag {
+                fields.push(Field::Bar);
+            }
+            if opts.full_in_flag {
+                fields.push(Field::FullIn);
+            }
+            fields.push(Field::Target);
+            if opts.options_flag {
+                fields.push(Field::Options);
+            }
+            // Mount points are first in a tree as in findmnt.
+            if opts.tree_flag {
+                fields.retain(|field| *field != Field::Target);
+                fields.insert(0, Field::Target);
             }
+            fields
         },
-        Err(err) => {
-            eprintln!("{}: {}", mount_entry.file.as_path().to_string_lossy(), err);
-            None
+    }
+}
+
+fn field_header(field: Field, opts: &Options) -> String
+{
+    let dialect = opts.compat.dialect();
+    match (field, opts.size_unit) {
+        (Field::Size, Some(SizeUnit::Blocks(block_size))) => format!("{}{}", format_block_size(block_size), gettext("-blocks")),
+        (Field::Size, None) => {
+            let block_size = if opts.kilo_flag { 1024 } else { dialect.default_block_size };
+            if dialect.is_numeric_block_size {
+                format!("{}{}", block_size, gettext("-blocks"))
+            } else {
+                format!("{}{}", format_block_size(block_size), gettext("-blocks"))
+            }
         },
+        _ => String::from(gettext(dialect.header(field))),
+    }
+}
+
+fn print_help(program_name: &str)
+{
+    println!("Usage: {} [OPTION]... [FILE]...", program_name);
+    println!("  or:  {} check [OPTION]...", program_name);
+    println!("  or:  {} daemon [OPTION]...", program_name);
+    println!("  or:  {} snapshot [OPTION]...", program_name);
+    println!("  or:  {} diff [OPTION]... OLD [NEW]", program_name);
+    #[cfg(feature = "sqlite")]
+    println!("  or:  {} history [OPTION]... TARGET", program_name);
+    println!("Show information about the filesystem on which each FILE resides,");
+    println!("or all filesystems by default.");
+    println!();
+    println!("Options:");
+    print_option_help(OPTIONS);
+    println!();
+    println!("Types of pseudo filesystems can be set in $XDG_CONFIG_HOME/mntdf/pseudo-fs, one type");
+    println!("per line. A TOML theme file of colors can be set by the theme key in");
+    println!("$XDG_CONFIG_HOME/mntdf/config.toml.");
+}
+
+fn print_version()
+{
+    println!("mntdf {}", env!("CARGO_PKG_VERSION"));
+    println!("Copyright (C) 2022-2023 Łukasz Szpakowski");
+    println!("License GPLv3: GNU GPL version 3 <http://gnu.org/licenses/gpl.html>.");
+    println!("This is free software: you are free to change and redistribute it.");
+    println!("There is NO WARRANTY, to the extent permitted by law.");
+}
+
+fn header_format_entry(fields: &[Field], opts: &Options) -> FormatEntry
+{
+    FormatEntry {
+        values: fields.iter().map(|field| field_header(*field, opts)).collect(),
+        styles: vec![None; fields.len()],
+    }
+}
+
+/// Returns the rounding mode of percentages. Percentages are rounded up by default so that a
+/// filesystem isn't reported as less full than it is.
+fn percent_rounding(opts: &Options) -> Rounding
+{
+    opts.rounding.unwrap_or(Rounding::Up)
+}
+
+/// Returns the rounding mode of sizes in blocks. By default, used sizes and total sizes are
+/// rounded up and available sizes are rounded down, so a sum of a used size and an available size
+/// can exceed a total size by one unit.
+fn size_rounding(is_rounded_up: bool, opts: &Options) -> Rounding
+{
+    match opts.rounding {
+        Some(rounding)        => rounding,
+        None if is_rounded_up => Rounding::Up,
+        None                  => Rounding::Down,
+    }
+}
+
+/// Returns the capacity in percents. The capacity exceeds 100% if the available size is negative.
+/// `None` is returned if the capacity is undefined.
+fn capacity_percent(used: u64, available: i128, rounding: Rounding) -> Option<u64>
+{
+    let total = i128::from(used) + available;
+    if total > 0 {
+        Some(rounding.div(u128::from(used) * 100, total as u128) as u64)
+    } else {
+        None
     }
 }
 
-fn calculate_format_max_lens(format_entries: &[FormatEntry]) -> FormatMaxLengths
+fn format_capacity(used: u64, available: i128, opts: &Options) -> String
+{
+    format!("{}%", capacity_percent(used, available, percent_rounding(opts)).unwrap_or(0))
+}
+
+/// Formats a size in the human-readable form. Human-readable sizes are always rounded up by
+/// default.
+fn format_human(size: u64, base: u64, opts: &Options) -> String
 {
-    let mut max_lens = FormatMaxLengths {
-        max_file_system_len: 0,
-        max_total_len: 0,
-        max_used_len: 0,
-        max_available_len: 0,
-        max_capacity_len: 0,
-        max_mount_point_len: 0,
+    let rounding = opts.rounding.unwrap_or(Rounding::Up);
+    let s = match opts.precision {
+        Some(precision) => format_human_size_with_precision(size, base, precision, rounding),
+        None            => format_human_size(size, base, rounding),
     };
-    for format_entry in format_entries.iter() {
-        max_lens.max_file_system_len = max(max_lens.max_file_system_len, format_entry.file_system.chars().fold(0, |x, _| x + 1));
-        max_lens.max_total_len = max(max_lens.max_total_len, format_entry.total.chars().fold(0, |x, _| x + 1));
-        max_lens.max_used_len = max(max_lens.max_used_len, format_entry.used.chars().fold(0, |x, _| x + 1));
-        max_lens.max_available_len = max(max_lens.max_available_len, format_entry.available.chars().fold(0, |x, _| x + 1));
-        max_lens.max_capacity_len = max(max_lens.max_capacity_len, format_entry.capacity.chars().fold(0, |x, _| x + 1));
-        max_lens.max_mount_point_len = max(max_lens.max_mount_point_len, format_entry.mount_point.chars().fold(0, |x, _| x + 1));
+    s.replace('.', opts.numeric_locale.decimal_point.as_str())
+}
+
+fn format_number(number: u64, opts: &Options) -> String
+{
+    if opts.group_digits_flag {
+        // The comma is used if the locale doesn't define a separator, because digit grouping is
+        // explicitly requested.
+        let thousands_sep = if !opts.numeric_locale.thousands_sep.is_empty() { opts.numeric_locale.thousands_sep.as_str() } else { "," };
+        format_grouped_number(number, thousands_sep)
+    } else {
+        format!("{}", number)
     }
-    max_lens
 }
 
-fn print_format_entries(format_entries: &[FormatEntry], max_lens: &FormatMaxLengths)
+fn format_count(count: u64, size_unit: Option<SizeUnit>, opts: &Options) -> String
 {
-    for format_entry in format_entries.iter() {
-        print!("{:<width$}", format_entry.file_system, width = max_lens.max_file_system_len);
-        print!(" ");
-        print!("{:>width$}", format_entry.total, width = max_lens.max_total_len);
-        print!(" ");
-        print!("{:>width$}", format_entry.used, width = max_lens.max_used_len);
-        print!(" ");
-        print!("{:>width$}", format_entry.available, width = max_lens.max_available_len);
-        print!(" ");
-        print!("{:>width$}", format_entry.capacity, width = max_lens.max_capacity_len);
-        print!(" ");
-        print!("{}", format_entry.mount_point);
-        println!("");
+    match size_unit {
+        Some(SizeUnit::Human(base)) => format_human(count, base, opts),
+        _                           => format_number(count, opts),
     }
 }
 
-fn main()
+/// Converts a filesystem identifier from statvfs to the form which is printed by `stat -f`, that is
+/// with the first word of the identifier in the high bits.
+fn fsid_to_u64(fsid: u64) -> u64
 {
-    let args: Vec<String> = env::args().collect();
-    let mut opt_parser = getopt::Parser::new(&args, "kP");
-    let mut opts = Options {
-        kilo_flag: false,
+    fsid.rotate_left(32)
+}
+
+/// Formats a capacity as a bar such as `[#######---]`, where the width is the number of characters
+/// between the brackets. A capacity above 100% fills the whole bar.
+fn format_bar(percent: Option<u64>, width: usize) -> String
+{
+    let filled = match percent {
+        Some(percent) => Rounding::Nearest.div(u128::from(percent.min(100)) * (width as u128), 100) as usize,
+        None          => 0,
     };
-    loop {
-        match opt_parser.next() {
-            Some(Ok(Opt('k', _))) => opts.kilo_flag = true,
-            Some(Ok(Opt('P', _))) => (),
-            Some(Ok(Opt(c, _))) => {
-                eprintln!("unknown option -- {:?}", c);
-                exit(1);
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+fn format_flags(flags: libc::c_ulong) -> String
+{
+    let mut names: Vec<&str> = vec![if flags & libc::ST_RDONLY != 0 { "ro" } else { "rw" }];
+    for (flag, name) in STATVFS_FLAGS.iter() {
+        if flags & *flag != 0 {
+            names.push(name);
+        }
+    }
+    names.join(",")
+}
+
+fn mount_option_name(mount_option: &MntOps) -> String
+{
+    match mount_option {
+        MntOps::Atime(true)     => String::from("atime"),
+        MntOps::Atime(false)    => String::from("noatime"),
+        MntOps::DirAtime(true)  => String::from("diratime"),
+        MntOps::DirAtime(false) => String::from("nodiratime"),
+        MntOps::RelAtime(true)  => String::from("relatime"),
+        MntOps::RelAtime(false) => String::from("norelatime"),
+        MntOps::Dev(true)       => String::from("dev"),
+        MntOps::Dev(false)      => String::from("nodev"),
+        MntOps::Exec(true)      => String::from("exec"),
+        MntOps::Exec(false)     => String::from("noexec"),
+        MntOps::Suid(true)      => String::from("suid"),
+        MntOps::Suid(false)     => String::from("nosuid"),
+        MntOps::Write(true)     => String::from("rw"),
+        MntOps::Write(false)    => String::from("ro"),
+        MntOps::Extra(s)        => s.clone(),
+    }
+}
+
+fn mount_options_string(mount_options: &[MntOps]) -> String
+{
+    mount_options.iter().map(mount_option_name).collect::<Vec<String>>().join(",")
+}
+
+fn mount_entry_mountinfo_entry<'a>(mount_entry: &MountEntry, mountinfo_entries: &'a [MountInfoEntry]) -> Option<&'a MountInfoEntry>
+{
+    find_mountinfo_entry(mountinfo_entries, unescape_mount_path(&mount_entry.file.as_path().to_string_lossy()))
+}
+
+fn is_bind_mount_entry(mount_entry: &MountEntry, mountinfo_entries: &[MountInfoEntry]) -> bool
+{
+    mount_entry_mountinfo_entry(mount_entry, mountinfo_entries).map(|mountinfo_entry| mountinfo_entry.is_bind_mount()).unwrap_or(false)
+}
+
+/// Finds an identifier of the device of the filesystem. The device is the source if the source is
+/// a block device, otherwise the device of the mount point.
+fn disk_id<F>(mount_entry: &MountEntry, device: Option<(u32, u32)>, opts: &Options, f: F) -> Option<String>
+    where F: Fn(&DiskIds, u64) -> Option<&str>
+{
+    let disk_ids = opts.disk_ids.as_ref()?;
+    let source_rdev = if mount_entry.spec.starts_with('/') {
+        fs::metadata(rooted_path(mount_entry.spec.as_str(), opts)).ok().filter(|metadata| metadata.file_type().is_block_device()).map(|metadata| metadata.rdev())
+    } else {
+        None
+    };
+    let rdev = source_rdev.or_else(|| device.map(|(major, minor)| libc::makedev(major, minor)))?;
+    f(disk_ids, rdev).map(String::from)
+}
+
+/// Returns the backing file of a loop device, for example an image of a snap package.
+fn loop_backing_file(source: &str) -> Option<String>
+{
+    let name = source.strip_prefix("/dev/")?;
+    if !name.starts_with("loop") {
+        return None;
+    }
+    let backing_file = fs::read_to_string(format!("/sys/block/{}/loop/backing_file", name)).ok()?;
+    Some(String::from(backing_file.trim_end_matches('\n')))
+}
+
+/// Returns the value of a mount option of a filesystem of the type, for example the `upperdir`
+/// option of an overlay filesystem.
+fn mount_option_value(mount_entry: &MountEntry, fs_type: &str, name: &str) -> Option<String>
+{
+    if mount_entry.vfstype != fs_type {
+        return None;
+    }
+    mount_entry.mntops.iter().find_map(|mount_option| {
+            match mount_option {
+                MntOps::Extra(s) => s.strip_prefix(name).and_then(|s| s.strip_prefix('=')).map(String::from),
+                _                => None,
+            }
+    })
+}
+
+/// Returns the usage entries of the filesystems of the layers of an overlay filesystem. The upper
+/// layer is the first layer.
+fn overlay_layer_usage_entries(usage_entry: &UsageEntry, mountinfo_entries: &[MountInfoEntry], opts: &Options) -> (Vec<UsageEntry>, i32)
+{
+    let mut layers: Vec<(&str, &str)> = Vec::new();
+    if let Some(upper_dir) = &usage_entry.upper_dir {
+        layers.push(("upper", upper_dir.as_str()));
+    }
+    if let Some(lower_dirs) = &usage_entry.lower_dirs {
+        layers.extend(lower_dirs.split(':').filter(|dir| !dir.is_empty()).map(|dir| ("lower", dir)));
+    }
+    let mut usage_entries: Vec<UsageEntry> = Vec::new();
+    let mut status = 0;
+    for (layer, dir) in layers {
+        let mount_entry = match fs::canonicalize(rooted_path(dir, opts)) {
+            Ok(canonical_path) => find_mount(unrooted_path(canonical_path, opts), opts.mtab.as_path()),
+            Err(err) => {
+                if !opts.quiet_flag {
+                    eprintln!("{}: {}", dir, err);
+                }
+                status = 1;
+                continue;
             },
-            Some(Err(err)) => {
+        };
+        match mount_entry {
+            Ok(Some(mount_entry)) => {
+                match mount_entry_to_usage_entry(&mount_entry, Some(Path::new(dir)), mountinfo_entries, opts, true) {
+                    Some(Some(mut layer_entry)) => {
+                        layer_entry.file_system = format!("{} ({})", layer_entry.file_system, layer);
+                        layer_entry.file = Some(String::from(dir));
+                        layer_entry.is_overlay_layer = true;
+                        usage_entries.push(layer_entry);
+                    },
+                    Some(None) => (),
+                    None       => status = 1,
+                }
+            },
+            Ok(None) => {
+                eprintln!("{}: {}", dir, gettext("Can't find mount entry"));
+                status = 1;
+            },
+            Err(err) => {
                 eprintln!("{}", err);
-                exit(1);
+                status = 1;
             },
-            None => break,
         }
     }
-    let mut status = 0;
-    let paths: Vec<&String> = args.iter().skip(opt_parser.index()).collect();
-    let mut format_entries: Vec<FormatEntry> = Vec::new();
-    format_entries.push(header_format_entry(&opts));
-    if !paths.is_empty() {
-        for path in paths {
-            match fs::metadata(path) {
-                Ok(_) => {
-                    match find_mount(path) {
-                        Ok(Some(mount_entry)) => {
-                            match mount_entry_to_format_entry(&mount_entry, &opts, true) {
-                                Some(Some(format_entry)) => format_entries.push(format_entry),
-                                Some(None)               => (),
-                                None                     => status = 1,
-                            }
-                        },
-                        Ok(None) => {
-                            eprintln!("{}: Can't find mount entry", path);
-                            status = 1;
-                        },
-                        Err(err) => {
-                            eprintln!("{}", err);
-                            status = 1;
-                        },
-                    }
+    (usage_entries, status)
+}
+
+fn mount_entry_to_usage_entry(mount_entry: &MountEntry, path: Option<&Path>, mountinfo_entries: &[MountInfoEntry], opts: &Options, is_vfs: bool) -> Option<Option<UsageEntry>>
+{
+    let stat_path = path.unwrap_or(mount_entry.file.as_path());
+    if opts.sync_flag {
+        // A failed synchronization doesn't prevent from reading the statistics.
+        let _ = syncfs(rooted_path(stat_path, opts));
+    }
+    // A path is stat'ed through its descriptor so that the statistics are for the filesystem of
+    // the path even if the mount entry is inaccurate.
+    let statvfs_res = match path {
+        Some(path) => fstatvfs_path(rooted_path(path, opts)),
+        None       => statvfs(rooted_path(mount_entry.file.as_path(), opts)),
+    };
+    match statvfs_res {
+        Ok(statvfs) => {
+            if opts.verbose_flag {
+                eprintln!("{}: f_bsize={} f_frsize={} f_blocks={} f_bfree={} f_bavail={} f_files={} f_ffree={} f_flag={:#x}",
+                    stat_path.to_string_lossy(), statvfs.bsize, statvfs.frsize, statvfs.blocks, statvfs.bfree, statvfs.bavail,
+                    statvfs.files, statvfs.ffree, statvfs.flag);
+            }
+            if statvfs.blocks != 0 || is_vfs {
+                let frsize = statvfs.frsize as u64;
+                let device = fs::metadata(rooted_path(stat_path, opts)).ok().map(|metadata| (libc::major(metadata.dev()), libc::minor(metadata.dev())));
+                let mountinfo_entry = mount_entry_mountinfo_entry(mount_entry, mountinfo_entries);
+                let is_bind_mount = mountinfo_entry.map(|mountinfo_entry| mountinfo_entry.is_bind_mount()).unwrap_or(false);
+                let file_system = match mountinfo_entry {
+                    Some(mountinfo_entry) if is_bind_mount && opts.bind_mount_mode == BindMountMode::Annotate => {
+                        format!("{}[{}]", mount_entry.spec, mountinfo_entry.root.as_path().to_string_lossy())
+                    },
+                    _ => mount_entry.spec.clone(),
+                };
+                Some(Some(UsageEntry {
+                        file: None,
+                        file_system,
+                        file_system_type: mount_entry.vfstype.clone(),
+                        mount_point: format!("{}", mount_entry.file.as_path().to_string_lossy()),
+                        mount_options: mount_options_string(&mount_entry.mntops),
+                        flags: Some(statvfs.flag as libc::c_ulong),
+                        fsid: Some(fsid_to_u64(statvfs.fsid as u64)),
+                        name_max: Some(statvfs.namemax as u64),
+                        device,
+                        uuid: disk_id(mount_entry, device, opts, DiskIds::uuid),
+                        label: disk_id(mount_entry, device, opts, DiskIds::label),
+                        backing_file: loop_backing_file(mount_entry.spec.as_str()),
+                        upper_dir: mount_option_value(mount_entry, "overlay", "upperdir"),
+                        lower_dirs: mount_option_value(mount_entry, "overlay", "lowerdir"),
+                        subvol: mount_option_value(mount_entry, "btrfs", "subvol"),
+                        subvol_id: mount_option_value(mount_entry, "btrfs", "subvolid"),
+                        server: remote_server(mount_entry),
+                        propagation: mountinfo_entry.map(|mountinfo_entry| mountinfo_entry.propagation()),
+                        mount_id: mountinfo_entry.map(|mountinfo_entry| mountinfo_entry.mount_id),
+                        parent_id: mountinfo_entry.map(|mountinfo_entry| mountinfo_entry.parent_id),
+                        is_bind_mount,
+                        is_overlay_layer: false,
+                        tree_prefix: String::new(),
+                        total_bytes: statvfs.blocks.saturating_mul(frsize),
+                        used_bytes: statvfs.blocks.saturating_sub(statvfs.bfree).saturating_mul(frsize),
+                        available_bytes: signed_blocks(statvfs.bavail).saturating_mul(frsize as i64),
+                        reserved_bytes: ((i128::from(statvfs.bfree) - i128::from(signed_blocks(statvfs.bavail))).clamp(0, i128::from(u64::MAX)) as u64).saturating_mul(frsize),
+                        total_inodes: statvfs.files,
+                        used_inodes: statvfs.files.saturating_sub(statvfs.ffree),
+                        available_inodes: statvfs.favail,
+                        full_in: None,
+                }))
+            } else {
+                Some(None)
+            }
+        },
+        Err(err) => {
+            if !opts.quiet_flag {
+                eprintln!("{}: {}", stat_path.to_string_lossy(), err);
+            }
+            None
+        },
+    }
+}
+
+/// Returns an entry with the sums of usage of the entries.
+fn sum_usage_entry<'a, I: Iterator<Item = &'a UsageEntry>>(file_system: &str, file_system_type: &str, usage_entries: I) -> UsageEntry
+{
+    let mut total_entry = UsageEntry {
+        file: None,
+        file_system: String::from(file_system),
+        file_system_type: String::from(file_system_type),
+        mount_point: String::from("-"),
+        mount_options: String::from("-"),
+        flags: None,
+        fsid: None,
+        name_max: None,
+        device: None,
+        uuid: None,
+        label: None,
+        backing_file: None,
+        upper_dir: None,
+        lower_dirs: None,
+        server: None,
+        propagation: None,
+        mount_id: None,
+        parent_id: None,
+        subvol: None,
+        subvol_id: None,
+        is_bind_mount: false,
+        is_overlay_layer: false,
+        tree_prefix: String::new(),
+        total_bytes: 0,
+        used_bytes: 0,
+        available_bytes: 0,
+        reserved_bytes: 0,
+        total_inodes: 0,
+        used_inodes: 0,
+        available_inodes: 0,
+        full_in: None,
+    };
+    // Bind mounts and layers of overlay filesystems are skipped because they duplicate usage of
+    // their filesystems.
+    for usage_entry in usage_entries.filter(|usage_entry| !usage_entry.is_bind_mount && !usage_entry.is_overlay_layer) {
+        total_entry.total_bytes += usage_entry.total_bytes;
+        total_entry.used_bytes += usage_entry.used_bytes;
+        total_entry.available_bytes += usage_entry.available_bytes;
+        total_entry.reserved_bytes += usage_entry.reserved_bytes;
+        total_entry.total_inodes += usage_entry.total_inodes;
+        total_entry.used_inodes += usage_entry.used_inodes;
+        total_entry.available_inodes += usage_entry.available_inodes;
+    }
+    total_entry
+}
+
+fn total_usage_entry(usage_entries: &[UsageEntry]) -> UsageEntry
+{
+    sum_usage_entry("total", "-", usage_entries.iter())
+}
+
+/// Returns entries with the sums of usage of filesystems grouped by the key. The entries are
+/// ordered by the key.
+fn summary_usage_entries(usage_entries: &[UsageEntry], summary_key: SummaryKey) -> Vec<UsageEntry>
+{
+    match summary_key {
+        SummaryKey::FsType => {
+            let mut fs_types: Vec<&str> = usage_entries.iter().map(|usage_entry| usage_entry.file_system_type.as_str()).collect();
+            fs_types.sort_unstable();
+            fs_types.dedup();
+            fs_types.iter().map(|fs_type| {
+                    sum_usage_entry(fs_type, fs_type, usage_entries.iter().filter(|usage_entry| usage_entry.file_system_type == *fs_type))
+            }).collect()
+        },
+    }
+}
+
+/// Converts a number of blocks to a signed number. Some filesystems report negative numbers of
+/// available blocks for unprivileged users which are stored as unsigned numbers.
+fn signed_blocks(blocks: u64) -> i64
+{
+    blocks as i64
+}
+
+fn format_signed_size(size: i64, is_rounded_up: bool, size_unit: Option<SizeUnit>, opts: &Options) -> String
+{
+    if size < 0 {
+        format!("-{}", format_size(size.unsigned_abs(), is_rounded_up, size_unit, opts))
+    } else {
+        format_size(size as u64, is_rounded_up, size_unit, opts)
+    }
+}
+
+fn format_size(size: u64, is_rounded_up: bool, size_unit: Option<SizeUnit>, opts: &Options) -> String
+{
+    match size_unit {
+        Some(SizeUnit::Human(base)) => format_human(size, base, opts),
+        _ => {
+            let unit_size = match size_unit {
+                Some(SizeUnit::Blocks(block_size)) => block_size,
+                _ if opts.kilo_flag                => 1024,
+                _                                  => opts.compat.dialect().default_block_size,
+            };
+            format_number(size_rounding(is_rounded_up, opts).div(size as u128, unit_size as u128) as u64, opts)
+        },
+    }
+}
+
+fn field_value(usage_entry: &UsageEntry, field: Field, opts: &Options) -> String
+{
+    field_value_in_unit(usage_entry, field, opts.size_unit, opts)
+}
+
+/// Returns the formatted value of the field with sizes in the specified unit.
+fn field_value_in_unit(usage_entry: &UsageEntry, field: Field, size_unit: Option<SizeUnit>, opts: &Options) -> String
+{
+    match field {
+        Field::Source      => usage_entry.file_system.clone(),
+        Field::FsType      => usage_entry.file_system_type.clone(),
+        Field::ITotal      => format_count(usage_entry.total_inodes, size_unit, opts),
+        Field::IUsed       => format_count(usage_entry.used_inodes, size_unit, opts),
+        Field::IAvail      => format_count(usage_entry.available_inodes, size_unit, opts),
+        Field::IPcent      => {
+            if usage_entry.total_inodes != 0 {
+                format_capacity(usage_entry.used_inodes, i128::from(usage_entry.available_inodes), opts)
+            } else {
+                String::from("-")
+            }
+        },
+        Field::Size        => format_size(usage_entry.total_bytes, true, size_unit, opts),
+        Field::Used        => format_size(usage_entry.used_bytes, true, size_unit, opts),
+        Field::Avail       => format_signed_size(usage_entry.available_bytes, false, size_unit, opts),
+        Field::Pcent       => format_capacity(usage_entry.used_bytes, i128::from(usage_entry.available_bytes), opts),
+        Field::Target      => {
+            if opts.mark_read_only_flag && usage_entry.is_read_only() {
+                format!("{}{} (ro)", usage_entry.tree_prefix, usage_entry.mount_point)
+            } else {
+                format!("{}{}", usage_entry.tree_prefix, usage_entry.mount_point)
+            }
+        },
+        Field::Reserved    => format_size(usage_entry.reserved_bytes, true, size_unit, opts),
+        Field::File        => usage_entry.file.clone().unwrap_or_else(|| String::from("-")),
+        Field::Options     => usage_entry.mount_options.clone(),
+        Field::Flags       => usage_entry.flags.map(format_flags).unwrap_or_else(|| String::from("-")),
+        Field::Fsid        => usage_entry.fsid.map(|fsid| format!("{:x}", fsid)).unwrap_or_else(|| String::from("-")),
+        Field::NameMax     => usage_entry.name_max.map(|name_max| format!("{}", name_max)).unwrap_or_else(|| String::from("-")),
+        Field::RawFlags    => usage_entry.flags.map(|flags| format!("{:#x}", flags)).unwrap_or_else(|| String::from("-")),
+        Field::MajMin      => usage_entry.device.map(|(major, minor)| format!("{}:{}", major, minor)).unwrap_or_else(|| String::from("-")),
+        Field::Uuid        => usage_entry.uuid.clone().unwrap_or_else(|| String::from("-")),
+        Field::Label       => usage_entry.label.clone().unwrap_or_else(|| String::from("-")),
+        Field::Backing     => usage_entry.backing_file.clone().unwrap_or_else(|| String::from("-")),
+        Field::UpperDir    => usage_entry.upper_dir.clone().unwrap_or_else(|| String::from("-")),
+        Field::LowerDir    => usage_entry.lower_dirs.clone().unwrap_or_else(|| String::from("-")),
+        Field::Server      => usage_entry.server.as_ref().map(|pair| pair.0.clone()).unwrap_or_else(|| String::from("-")),
+        Field::Export      => usage_entry.server.as_ref().map(|pair| pair.1.clone()).unwrap_or_else(|| String::from("-")),
+        Field::Propagation => usage_entry.propagation.clone().unwrap_or_else(|| String::from("-")),
+        Field::Id          => usage_entry.mount_id.map(|id| format!("{}", id)).unwrap_or_else(|| String::from("-")),
+        Field::Parent      => usage_entry.parent_id.map(|id| format!("{}", id)).unwrap_or_else(|| String::from("-")),
+        Field::Subvol      => usage_entry.subvol.clone().unwrap_or_else(|| String::from("-")),
+        Field::SubvolId    => usage_entry.subvol_id.clone().unwrap_or_else(|| String::from("-")),
+        Field::Bar         => format_bar(usage_entry.capacity_percent(opts), opts.bar_width),
+        Field::FullIn      => {
+            match usage_entry.full_in {
+                Some(full_in) if usage_entry.is_full_soon(opts) => format!("{}!", format_duration(full_in)),
+                Some(full_in)                                   => format_duration(full_in),
+                None                                            => String::from("-"),
+            }
+        },
+    }
+}
+
+/// Returns the raw value of the field for structured output formats. Sizes are in bytes and
+/// undefined values are null.
+fn field_raw_value(usage_entry: &UsageEntry, field: Field, opts: &Options) -> Value
+{
+    match field {
+        Field::Source      => Value::String(usage_entry.file_system.clone()),
+        Field::FsType      => Value::String(usage_entry.file_system_type.clone()),
+        Field::ITotal      => Value::Integer(i128::from(usage_entry.total_inodes)),
+        Field::IUsed       => Value::Integer(i128::from(usage_entry.used_inodes)),
+        Field::IAvail      => Value::Integer(i128::from(usage_entry.available_inodes)),
+        Field::IPcent      => {
+            if usage_entry.total_inodes != 0 {
+                Value::from_option_integer(capacity_percent(usage_entry.used_inodes, i128::from(usage_entry.available_inodes), percent_rounding(opts)))
+            } else {
+                Value::Null
+            }
+        },
+        Field::Size        => Value::Integer(i128::from(usage_entry.total_bytes)),
+        Field::Used        => Value::Integer(i128::from(usage_entry.used_bytes)),
+        Field::Avail       => Value::Integer(i128::from(usage_entry.available_bytes)),
+        Field::Pcent       => Value::from_option_integer(capacity_percent(usage_entry.used_bytes, i128::from(usage_entry.available_bytes), percent_rounding(opts))),
+        Field::Target      => Value::String(usage_entry.mount_point.clone()),
+        Field::File        => Value::from_option_string(usage_entry.file.as_ref()),
+        Field::Reserved    => Value::Integer(i128::from(usage_entry.reserved_bytes)),
+        Field::Options     => Value::String(usage_entry.mount_options.clone()),
+        Field::Flags       => Value::from_option_string(usage_entry.flags.map(format_flags).as_ref()),
+        Field::Fsid        => Value::from_option_string(usage_entry.fsid.map(|fsid| format!("{:x}", fsid)).as_ref()),
+        Field::NameMax     => Value::from_option_integer(usage_entry.name_max),
+        Field::RawFlags    => Value::from_option_integer(usage_entry.flags),
+        Field::MajMin      => Value::from_option_string(usage_entry.device.map(|(major, minor)| format!("{}:{}", major, minor)).as_ref()),
+        Field::Uuid        => Value::from_option_string(usage_entry.uuid.as_ref()),
+        Field::Label       => Value::from_option_string(usage_entry.label.as_ref()),
+        Field::Backing     => Value::from_option_string(usage_entry.backing_file.as_ref()),
+        Field::UpperDir    => Value::from_option_string(usage_entry.upper_dir.as_ref()),
+        Field::LowerDir    => Value::from_option_string(usage_entry.lower_dirs.as_ref()),
+        Field::Server      => Value::from_option_string(usage_entry.server.as_ref().map(|pair| &pair.0)),
+        Field::Export      => Value::from_option_string(usage_entry.server.as_ref().map(|pair| &pair.1)),
+        Field::Propagation => Value::from_option_string(usage_entry.propagation.as_ref()),
+        Field::Id          => Value::from_option_integer(usage_entry.mount_id),
+        Field::Parent      => Value::from_option_integer(usage_entry.parent_id),
+        Field::Subvol      => Value::from_option_string(usage_entry.subvol.as_ref()),
+        Field::SubvolId    => {
+            match usage_entry.subvol_id.as_ref().map(|id| id.parse::<u64>()) {
+                Some(Ok(id)) => Value::Integer(i128::from(id)),
+                _            => Value::from_option_string(usage_entry.subvol_id.as_ref()),
+            }
+        },
+        Field::Bar         => Value::String(format_bar(usage_entry.capacity_percent(opts), opts.bar_width)),
+        Field::FullIn      => Value::from_option_integer(usage_entry.full_in),
+    }
+}
+
+fn compare_usage_entries(usage_entry1: &UsageEntry, usage_entry2: &UsageEntry, field: Field, opts: &Options) -> Ordering
+{
+    let rounding = percent_rounding(opts);
+    match field {
+        Field::Source      => usage_entry1.file_system.cmp(&usage_entry2.file_system),
+        Field::FsType      => usage_entry1.file_system_type.cmp(&usage_entry2.file_system_type),
+        Field::ITotal      => usage_entry1.total_inodes.cmp(&usage_entry2.total_inodes),
+        Field::IUsed       => usage_entry1.used_inodes.cmp(&usage_entry2.used_inodes),
+        Field::IAvail      => usage_entry1.available_inodes.cmp(&usage_entry2.available_inodes),
+        Field::IPcent      => {
+            capacity_percent(usage_entry1.used_inodes, i128::from(usage_entry1.available_inodes), rounding)
+                .cmp(&capacity_percent(usage_entry2.used_inodes, i128::from(usage_entry2.available_inodes), rounding))
+        },
+        Field::Size        => usage_entry1.total_bytes.cmp(&usage_entry2.total_bytes),
+        Field::Used        => usage_entry1.used_bytes.cmp(&usage_entry2.used_bytes),
+        Field::Avail       => usage_entry1.available_bytes.cmp(&usage_entry2.available_bytes),
+        Field::Pcent       => {
+            capacity_percent(usage_entry1.used_bytes, i128::from(usage_entry1.available_bytes), rounding)
+                .cmp(&capacity_percent(usage_entry2.used_bytes, i128::from(usage_entry2.available_bytes), rounding))
+        },
+        Field::Target      => usage_entry1.mount_point.cmp(&usage_entry2.mount_point),
+        Field::File        => usage_entry1.file.cmp(&usage_entry2.file),
+        Field::Reserved    => usage_entry1.reserved_bytes.cmp(&usage_entry2.reserved_bytes),
+        Field::Options     => usage_entry1.mount_options.cmp(&usage_entry2.mount_options),
+        Field::Flags       => usage_entry1.flags.map(format_flags).cmp(&usage_entry2.flags.map(format_flags)),
+        Field::Fsid        => usage_entry1.fsid.cmp(&usage_entry2.fsid),
+        Field::NameMax     => usage_entry1.name_max.cmp(&usage_entry2.name_max),
+        Field::RawFlags    => usage_entry1.flags.cmp(&usage_entry2.flags),
+        Field::MajMin      => usage_entry1.device.cmp(&usage_entry2.device),
+        Field::Uuid        => usage_entry1.uuid.cmp(&usage_entry2.uuid),
+        Field::Label       => usage_entry1.label.cmp(&usage_entry2.label),
+        Field::Backing     => usage_entry1.backing_file.cmp(&usage_entry2.backing_file),
+        Field::UpperDir    => usage_entry1.upper_dir.cmp(&usage_entry2.upper_dir),
+        Field::LowerDir    => usage_entry1.lower_dirs.cmp(&usage_entry2.lower_dirs),
+        Field::Server      => usage_entry1.server.as_ref().map(|pair| &pair.0).cmp(&usage_entry2.server.as_ref().map(|pair| &pair.0)),
+        Field::Export      => usage_entry1.server.as_ref().map(|pair| &pair.1).cmp(&usage_entry2.server.as_ref().map(|pair| &pair.1)),
+        Field::Propagation => usage_entry1.propagation.cmp(&usage_entry2.propagation),
+        Field::Id          => usage_entry1.mount_id.cmp(&usage_entry2.mount_id),
+        Field::Parent      => usage_entry1.parent_id.cmp(&usage_entry2.parent_id),
+        Field::Subvol      => usage_entry1.subvol.cmp(&usage_entry2.subvol),
+        Field::SubvolId    => usage_entry1.subvol_id.cmp(&usage_entry2.subvol_id),
+        Field::Bar         => {
+            capacity_percent(usage_entry1.used_bytes, i128::from(usage_entry1.available_bytes), rounding)
+                .cmp(&capacity_percent(usage_entry2.used_bytes, i128::from(usage_entry2.available_bytes), rounding))
+        },
+        // Filesystems which don't grow are last.
+        Field::FullIn      => usage_entry1.full_in.unwrap_or(u64::MAX).cmp(&usage_entry2.full_in.unwrap_or(u64::MAX)),
+    }
+}
+
+/// Orders usage entries so that entries of child mounts directly follow entries of their parent
+/// mounts. The order of siblings is preserved.
+///
+/// Parents are found by parent IDs from mountinfo. If a parent isn't among the entries, for
+/// example when the parent is overmounted, the entry with the longest mount point containing the
+/// mount point is the parent.
+///
+/// If `is_tree` is `true`, tree prefixes of mount points are set for the entries. The tree lines
+/// are drawn by ASCII characters if `is_ascii` is `true`.
+fn hierarchy_order(usage_entries: Vec<UsageEntry>, is_tree: bool, is_ascii: bool) -> Vec<UsageEntry>
+{
+    let (branch, last_branch, line) = if is_ascii { ("|-", "`-", "| ") } else { ("├─", "└─", "│ ") };
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); usage_entries.len()];
+    let mut roots: Vec<usize> = Vec::new();
+    for (i, usage_entry) in usage_entries.iter().enumerate() {
+        let parent_index = usage_entry.parent_id.filter(|parent_id| Some(*parent_id) != usage_entry.mount_id).and_then(|parent_id| {
+                usage_entries.iter().position(|parent_entry| parent_entry.mount_id == Some(parent_id))
+        }).or_else(|| {
+                let mount_point = Path::new(usage_entry.mount_point.as_str());
+                usage_entries.iter().enumerate()
+                    .filter(|(_, parent_entry)| parent_entry.mount_point != usage_entry.mount_point && mount_point.starts_with(parent_entry.mount_point.as_str()))
+                    .max_by_key(|(_, parent_entry)| parent_entry.mount_point.len())
+                    .map(|(j, _)| j)
+        });
+        match parent_index {
+            Some(j) => children[j].push(i),
+            None    => roots.push(i),
+        }
+    }
+    let mut entries: Vec<Option<UsageEntry>> = usage_entries.into_iter().map(Some).collect();
+    let mut ordered_entries: Vec<UsageEntry> = Vec::new();
+    // The stack has the entry indices with the tree prefixes of the entries and the prefixes of
+    // their children.
+    let mut stack: Vec<(usize, String, String)> = roots.into_iter().rev().map(|i| (i, String::new(), String::new())).collect();
+    while let Some((i, prefix, child_prefix)) = stack.pop() {
+        if let Some(mut usage_entry) = entries[i].take() {
+            for (k, j) in children[i].iter().enumerate().rev() {
+                if k + 1 == children[i].len() {
+                    stack.push((*j, format!("{}{}", child_prefix, last_branch), format!("{}  ", child_prefix)));
+                } else {
+                    stack.push((*j, format!("{}{}", child_prefix, branch), format!("{}{}", child_prefix, line)));
+                }
+            }
+            if is_tree {
+                usage_entry.tree_prefix = prefix;
+            }
+            ordered_entries.push(usage_entry);
+        }
+    }
+    // Entries in cycles aren't reachable from roots.
+    ordered_entries.extend(entries.into_iter().flatten());
+    ordered_entries
+}
+
+/// Returns the SGR parameters of the field value if the output is colored. Capacities are colored
+/// according to usage and pseudo filesystems are dimmed by default, which can be changed by a
+/// theme.
+fn field_style(usage_entry: &UsageEntry, field: Field, opts: &Options) -> Option<String>
+{
+    if !opts.color_flag {
+        return None;
+    }
+    let theme = &opts.theme;
+    let mut sgrs: Vec<&str> = Vec::new();
+    let is_dimmed = (theme.is_pseudo_dimmed && opts.pseudo_types.contains(&usage_entry.file_system_type)) ||
+        (theme.is_read_only_dimmed && usage_entry.is_read_only()) ||
+        theme.dimmed_types.contains(&usage_entry.file_system_type);
+    if is_dimmed {
+        sgrs.push(theme.dim_style.as_str());
+    }
+    if let Some(style) = theme.column_style(field) {
+        sgrs.push(style);
+    }
+    if let Some(style) = field_usage_percent(usage_entry, field, opts).and_then(|percent| theme.capacity_style(percent)) {
+        sgrs.push(style);
+    }
+    // A filesystem which is estimated to be full soon has the style of full capacity.
+    if field == Field::FullIn && usage_entry.is_full_soon(opts) {
+        if let Some(style) = theme.capacity_style(100) {
+            sgrs.push(style);
+        }
+    }
+    sgrs.retain(|sgr| !sgr.is_empty());
+    if !sgrs.is_empty() {
+        Some(sgrs.join(";"))
+    } else {
+        None
+    }
+}
+
+fn usage_entry_to_format_entry(usage_entry: &UsageEntry, fields: &[Field], opts: &Options) -> FormatEntry
+{
+    FormatEntry {
+        values: fields.iter().map(|field| field_value(usage_entry, *field, opts)).collect(),
+        styles: fields.iter().map(|field| field_style(usage_entry, *field, opts)).collect(),
+    }
+}
+
+/// Pads a value with spaces to the display width. Wide characters such as CJK characters take two
+/// columns.
+fn pad_value(value: &str, width: usize, is_left_aligned: bool) -> String
+{
+    let padding = " ".repeat(width.saturating_sub(value.width()));
+    if is_left_aligned {
+        format!("{}{}", value, padding)
+    } else {
+        format!("{}{}", padding, value)
+    }
+}
+
+/// Truncates a value to the display width. The end of a truncated value is replaced by an
+/// ellipsis.
+fn truncate_value(value: &str, width: usize, ellipsis: &str) -> String
+{
+    if value.width() <= width {
+        return String::from(value);
+    }
+    let max_width = width.saturating_sub(ellipsis.width());
+    let mut s = String::new();
+    let mut s_width = 0;
+    for c in value.chars() {
+        let c_width = c.width().unwrap_or(0);
+        if s_width + c_width > max_width {
+            break;
+        }
+        s.push(c);
+        s_width += c_width;
+    }
+    s.push_str(ellipsis);
+    s
+}
+
+/// Truncates values of the truncated fields so that the table fits in the terminal width. The
+/// fields are truncated in the order of the columns, but not below the minimal width.
+fn truncate_format_entries(format_entries: &mut [FormatEntry], fields: &[Field], max_lens: &mut [usize], terminal_width: usize, truncated_fields: &[Field], ellipsis: &str)
+{
+    let table_width = max_lens.iter().sum::<usize>() + fields.len().saturating_sub(1);
+    let mut overflow = table_width.saturating_sub(terminal_width);
+    for (i, field) in fields.iter().enumerate() {
+        if overflow == 0 {
+            break;
+        }
+        if truncated_fields.contains(field) && max_lens[i] > MIN_TRUNCATED_WIDTH {
+            let reduction = min(overflow, max_lens[i] - MIN_TRUNCATED_WIDTH);
+            max_lens[i] -= reduction;
+            overflow -= reduction;
+            for format_entry in format_entries.iter_mut() {
+                format_entry.values[i] = truncate_value(format_entry.values[i].as_str(), max_lens[i], ellipsis);
+            }
+        }
+    }
+}
+
+fn calculate_format_max_lens(format_entries: &[FormatEntry], fields: &[Field]) -> Vec<usize>
+{
+    let mut max_lens: Vec<usize> = vec![0; fields.len()];
+    for format_entry in format_entries.iter() {
+        for (max_len, value) in max_lens.iter_mut().zip(format_entry.values.iter()) {
+            *max_len = max(*max_len, value.width());
+        }
+    }
+    max_lens
+}
+
+/// Prints the format entries as a table. If `is_first_wrapped` is `true`, a value of the first
+/// column that is wider than the column is printed in its own line and the other values are
+/// printed in the next line as GNU df does.
+fn print_format_entries(format_entries: &[FormatEntry], fields: &[Field], max_lens: &[usize], is_first_wrapped: bool)
+{
+    for format_entry in format_entries.iter() {
+        for (i, ((field, max_len), (value, style))) in fields.iter().zip(max_lens.iter()).zip(format_entry.values.iter().zip(format_entry.styles.iter())).enumerate() {
+            if i > 0 {
+                print!(" ");
+            }
+            let padded_value = if i == 0 && is_first_wrapped && fields.len() > 1 && value.width() > *max_len {
+                format!("{}\n{}", value, " ".repeat(*max_len))
+            } else if i + 1 == fields.len() && field.is_left_aligned() {
+                value.clone()
+            } else {
+                pad_value(value.as_str(), *max_len, field.is_left_aligned())
+            };
+            match style {
+                Some(style) => print!("{}", colorize(padded_value.as_str(), style.as_str())),
+                None        => print!("{}", padded_value),
+            }
+        }
+        println!();
+    }
+}
+
+/// Prints the format entries with values separated by the separator instead of padding.
+fn print_separated_format_entries(format_entries: &[FormatEntry], separator: &str)
+{
+    for format_entry in format_entries.iter() {
+        let values: Vec<String> = format_entry.values.iter().zip(format_entry.styles.iter()).map(|(value, style)| {
+                match style {
+                    Some(style) => colorize(value.as_str(), style.as_str()),
+                    None        => value.clone(),
+                }
+        }).collect();
+        println!("{}", values.join(separator));
+    }
+}
+
+fn usage_entry_to_json(usage_entry: &UsageEntry, fields: &[Field], opts: &Options) -> String
+{
+    let pairs: Vec<(&str, Value)> = fields.iter().map(|field| (field.name(), field_raw_value(usage_entry, *field, opts))).collect();
+    json_object(pairs.as_slice())
+}
+
+fn print_markdown(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    // The header row is required in Markdown tables.
+    let headers: Vec<String> = fields.iter().map(|field| field_header(*field, opts)).collect();
+    println!("{}", markdown_row(headers.as_slice()));
+    let left_alignments: Vec<bool> = fields.iter().map(|field| field.is_left_aligned()).collect();
+    println!("{}", markdown_delimiter_row(left_alignments.as_slice()));
+    for usage_entry in usage_entries.iter() {
+        println!("{}", markdown_row(usage_entry_to_format_entry(usage_entry, fields, opts).values.as_slice()));
+    }
+}
+
+fn field_usage_percent(usage_entry: &UsageEntry, field: Field, opts: &Options) -> Option<u64>
+{
+    match field {
+        Field::Bar                                     => usage_entry.capacity_percent(opts),
+        Field::Pcent                                   => capacity_percent(usage_entry.used_bytes, i128::from(usage_entry.available_bytes), percent_rounding(opts)),
+        Field::IPcent if usage_entry.total_inodes != 0 => capacity_percent(usage_entry.used_inodes, i128::from(usage_entry.available_inodes), percent_rounding(opts)),
+        _                                              => None,
+    }
+}
+
+fn print_html(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    println!("<!DOCTYPE html>");
+    println!("<html>");
+    println!("<head>");
+    println!("<meta charset=\"utf-8\">");
+    println!("<title>Filesystems</title>");
+    println!("</head>");
+    println!("<body>");
+    println!("<table>");
+    if !opts.no_header_flag {
+        println!("<thead>");
+        let cells: Vec<String> = fields.iter().map(|field| format!("<th>{}</th>", html_escape(field_header(*field, opts).as_str()))).collect();
+        println!("<tr>{}</tr>", cells.join(""));
+        println!("</thead>");
+    }
+    println!("<tbody>");
+    for usage_entry in usage_entries.iter() {
+        let mut cells: Vec<String> = Vec::new();
+        for field in fields.iter() {
+            let mut styles: Vec<String> = Vec::new();
+            if !field.is_left_aligned() {
+                styles.push(String::from("text-align: right"));
+            }
+            if opts.html_usage_style_flag {
+                if let Some(percent) = field_usage_percent(usage_entry, *field, opts) {
+                    styles.push(html_usage_style(percent));
+                }
+            }
+            let value = html_escape(field_value(usage_entry, *field, opts).as_str());
+            if styles.is_empty() {
+                cells.push(format!("<td>{}</td>", value));
+            } else {
+                cells.push(format!("<td style=\"{}\">{}</td>", html_escape(styles.join("; ").as_str()), value));
+            }
+        }
+        println!("<tr>{}</tr>", cells.join(""));
+    }
+    println!("</tbody>");
+    println!("</table>");
+    println!("</body>");
+    println!("</html>");
+}
+
+fn print_null_separated(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    let mut format_entries: Vec<FormatEntry> = Vec::new();
+    if !opts.no_header_flag {
+        format_entries.push(header_format_entry(fields, opts));
+    }
+    for usage_entry in usage_entries.iter() {
+        format_entries.push(usage_entry_to_format_entry(usage_entry, fields, opts));
+    }
+    let mut stdout = stdout().lock();
+    for format_entry in format_entries.iter() {
+        for value in format_entry.values.iter() {
+            let _ = stdout.write_all(value.as_bytes());
+            let _ = stdout.write_all(b"\0");
+        }
+        let _ = stdout.write_all(b"\0");
+    }
+}
+
+fn print_records(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    let headers: Vec<String> = fields.iter().map(|field| format!("{}:", field_header(*field, opts))).collect();
+    let max_header_len = headers.iter().fold(0, |x, header| max(x, header.width()));
+    for (i, usage_entry) in usage_entries.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        for (header, field) in headers.iter().zip(fields.iter()) {
+            println!("{} {}", pad_value(header.as_str(), max_header_len, true), field_value(usage_entry, *field, opts));
+        }
+    }
+}
+
+fn print_template(usage_entries: &[UsageEntry], opts: &Options)
+{
+    for usage_entry in usage_entries.iter() {
+        let mut s = String::new();
+        for item in opts.template.iter() {
+            match item {
+                TemplateItem::Text(text)                          => s.push_str(text.as_str()),
+                TemplateItem::Placeholder(field, Some(size_unit)) => s.push_str(field_value_in_unit(usage_entry, *field, Some(*size_unit), opts).as_str()),
+                TemplateItem::Placeholder(field, None)            => s.push_str(field_value(usage_entry, *field, opts).as_str()),
+            }
+        }
+        println!("{}", s);
+    }
+}
+
+fn print_json(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    if usage_entries.is_empty() {
+        println!("[]");
+        return;
+    }
+    println!("[");
+    for (i, usage_entry) in usage_entries.iter().enumerate() {
+        let separator = if i + 1 < usage_entries.len() { "," } else { "" };
+        println!("  {}{}", usage_entry_to_json(usage_entry, fields, opts), separator);
+    }
+    println!("]");
+}
+
+fn print_json_lines(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    for usage_entry in usage_entries.iter() {
+        println!("{}", usage_entry_to_json(usage_entry, fields, opts));
+    }
+}
+
+/// Adds a usage entry. A streamed entry is also printed at once if it isn't filtered out, so that
+/// results are printed incrementally when getting usage of some filesystems is slow.
+fn add_usage_entry(usage_entries: &mut Vec<UsageEntry>, usage_entry: UsageEntry, fields: &[Field], is_streamed: bool, opts: &Options)
+{
+    if is_streamed && opts.over_percent.map(|over_percent| usage_entry.capacity(opts) >= over_percent).unwrap_or(true) {
+        println!("{}", usage_entry_to_json(&usage_entry, fields, opts));
+    }
+    usage_entries.push(usage_entry);
+}
+
+fn print_csv(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    if !opts.no_header_flag {
+        let names: Vec<&str> = fields.iter().map(|field| field.name()).collect();
+        println!("{}", csv_record(names.as_slice()));
+    }
+    for usage_entry in usage_entries.iter() {
+        let values: Vec<String> = fields.iter().map(|field| field_raw_value(usage_entry, *field, opts).to_string()).collect();
+        println!("{}", csv_record(values.as_slice()));
+    }
+}
+
+fn print_tsv(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    if !opts.no_header_flag {
+        let names: Vec<&str> = fields.iter().map(|field| field.name()).collect();
+        println!("{}", tsv_record(names.as_slice()));
+    }
+    for usage_entry in usage_entries.iter() {
+        let values: Vec<String> = fields.iter().map(|field| field_raw_value(usage_entry, *field, opts).to_string()).collect();
+        println!("{}", tsv_record(values.as_slice()));
+    }
+}
+
+fn print_yaml(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    if usage_entries.is_empty() {
+        println!("[]");
+        return;
+    }
+    for usage_entry in usage_entries.iter() {
+        let pairs: Vec<(&str, Value)> = fields.iter().map(|field| (field.name(), field_raw_value(usage_entry, *field, opts))).collect();
+        print!("{}", yaml_sequence_item(pairs.as_slice()));
+    }
+}
+
+/// Prints assignments of variables such as `MNTDF_0_TARGET` which can be evaluated by a shell. The
+/// `MNTDF_COUNT` variable is the number of filesystems.
+fn print_shell(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    for (i, usage_entry) in usage_entries.iter().enumerate() {
+        let assignments: Vec<String> = fields.iter().map(|field| {
+                format!("MNTDF_{}_{}={}", i, field.name().to_uppercase(), shell_value(&field_raw_value(usage_entry, *field, opts)))
+        }).collect();
+        println!("{};", assignments.join("; "));
+    }
+    println!("MNTDF_COUNT={};", usage_entries.len());
+}
+
+/// Prints metrics with the names and the labels of metrics of the filesystem collector of
+/// node_exporter, so that the output can be read by the textfile collector.
+fn prometheus_text(usage_entries: &[UsageEntry]) -> String
+{
+    let mut lines: Vec<String> = Vec::new();
+    type MetricValue = fn(&UsageEntry) -> i128;
+    let metrics: &[(&str, &str, MetricValue)] = &[
+        ("node_filesystem_size_bytes", "Filesystem size in bytes.", |usage_entry| i128::from(usage_entry.total_bytes)),
+        ("node_filesystem_free_bytes", "Filesystem free space in bytes.", |usage_entry| i128::from(usage_entry.total_bytes - usage_entry.used_bytes)),
+        ("node_filesystem_avail_bytes", "Filesystem space available to non-root users in bytes.", |usage_entry| i128::from(usage_entry.available_bytes)),
+        ("node_filesystem_files", "Filesystem total file nodes.", |usage_entry| i128::from(usage_entry.total_inodes)),
+        ("node_filesystem_files_free", "Filesystem total free file nodes.", |usage_entry| i128::from(usage_entry.total_inodes - usage_entry.used_inodes)),
+        ("node_filesystem_readonly", "Filesystem read-only status.", |usage_entry| i128::from(usage_entry.is_read_only())),
+    ];
+    for (name, help, metric_value) in metrics.iter() {
+        lines.push(format!("# HELP {} {}", name, help));
+        lines.push(format!("# TYPE {} gauge", name));
+        for usage_entry in usage_entries.iter() {
+            let labels = [
+                ("device", usage_entry.file_system.as_str()),
+                ("fstype", usage_entry.file_system_type.as_str()),
+                ("mountpoint", usage_entry.mount_point.as_str()),
+            ];
+            lines.push(prometheus_sample(name, &labels, metric_value(usage_entry)));
+        }
+    }
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Prints points of the `disk` measurement with the tags and the fields of the disk input of
+/// Telegraf. All points have the same timestamp in nanoseconds.
+fn print_influx(usage_entries: &[UsageEntry])
+{
+    let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|duration| duration.as_nanos()).unwrap_or(0);
+    for usage_entry in usage_entries.iter() {
+        let available_bytes = usage_entry.available_bytes.max(0) as u64;
+        let sum = u128::from(usage_entry.used_bytes) + u128::from(available_bytes);
+        let used_hundredths = if sum != 0 { Rounding::Nearest.div(u128::from(usage_entry.used_bytes) * 10000, sum) } else { 0 };
+        let device = usage_entry.file_system.strip_prefix("/dev/").unwrap_or(usage_entry.file_system.as_str());
+        let tags = [
+            ("path", usage_entry.mount_point.as_str()),
+            ("device", device),
+            ("fstype", usage_entry.file_system_type.as_str()),
+            ("mode", if usage_entry.is_read_only() { "ro" } else { "rw" }),
+        ];
+        let fields = [
+            ("total", format!("{}i", usage_entry.total_bytes)),
+            ("free", format!("{}i", usage_entry.total_bytes - usage_entry.used_bytes)),
+            ("used", format!("{}i", usage_entry.used_bytes)),
+            ("used_percent", format!("{}.{:02}", used_hundredths / 100, used_hundredths % 100)),
+            ("inodes_total", format!("{}i", usage_entry.total_inodes)),
+            ("inodes_free", format!("{}i", usage_entry.total_inodes - usage_entry.used_inodes)),
+            ("inodes_used", format!("{}i", usage_entry.used_inodes)),
+        ];
+        println!("{}", influx_line("disk", &tags, &fields, timestamp));
+    }
+}
+
+/// Returns gauges of usage of filesystems for StatsD, for example `mntdf.var_log.used`. Negative
+/// available space is sent as zero because negative gauges are decrements in StatsD.
+fn usage_entries_to_statsd(usage_entries: &[UsageEntry], opts: &Options) -> Vec<String>
+{
+    let mut metrics: Vec<String> = Vec::new();
+    for usage_entry in usage_entries.iter() {
+        let name = format!("mntdf.{}", statsd_name(usage_entry.mount_point.as_str()));
+        let values = [
+            ("size", usage_entry.total_bytes),
+            ("used", usage_entry.used_bytes),
+            ("avail", usage_entry.available_bytes.max(0) as u64),
+            ("pcent", capacity_percent(usage_entry.used_bytes, i128::from(usage_entry.available_bytes), percent_rounding(opts)).unwrap_or(0)),
+            ("itotal", usage_entry.total_inodes),
+            ("iused", usage_entry.used_inodes),
+            ("iavail", usage_entry.available_inodes),
+            ("ipcent", capacity_percent(usage_entry.used_inodes, i128::from(usage_entry.available_inodes), percent_rounding(opts)).unwrap_or(0)),
+        ];
+        for (field_name, value) in values.iter() {
+            metrics.push(statsd_gauge(format!("{}.{}", name, field_name).as_str(), *value));
+        }
+    }
+    metrics
+}
+
+/// Returns an OTLP request with gauges of usage of filesystems which have names and attributes
+/// of the semantic conventions for system metrics of OpenTelemetry.
+#[cfg(feature = "otlp")]
+fn usage_entries_to_otlp(usage_entries: &[UsageEntry]) -> String
+{
+    let mut usage_gauge = OtlpGauge { name: "system.filesystem.usage", unit: "By", data_points: Vec::new(), };
+    let mut utilization_gauge = OtlpGauge { name: "system.filesystem.utilization", unit: "1", data_points: Vec::new(), };
+    let mut inode_usage_gauge = OtlpGauge { name: "system.filesystem.inodes.usage", unit: "{inode}", data_points: Vec::new(), };
+    for usage_entry in usage_entries.iter() {
+        let attributes = vec![
+            ("system.device", usage_entry.file_system.clone()),
+            ("system.filesystem.mountpoint", usage_entry.mount_point.clone()),
+            ("system.filesystem.type", usage_entry.file_system_type.clone()),
+            ("system.filesystem.mode", String::from(if usage_entry.is_read_only() { "ro" } else { "rw" })),
+        ];
+        let with_state = |state: &str| {
+            let mut state_attributes = attributes.clone();
+            state_attributes.push(("system.filesystem.state", String::from(state)));
+            state_attributes
+        };
+        let available_bytes = usage_entry.available_bytes.max(0) as u64;
+        usage_gauge.data_points.push(OtlpDataPoint { attributes: with_state("used"), value: OtlpValue::Int(usage_entry.used_bytes), });
+        usage_gauge.data_points.push(OtlpDataPoint { attributes: with_state("free"), value: OtlpValue::Int(available_bytes), });
+        usage_gauge.data_points.push(OtlpDataPoint { attributes: with_state("reserved"), value: OtlpValue::Int(usage_entry.reserved_bytes), });
+        let utilization = if usage_entry.total_bytes != 0 {
+            Rounding::Nearest.div(u128::from(usage_entry.used_bytes) * 10000, u128::from(usage_entry.total_bytes)) as u64
+        } else {
+            0
+        };
+        utilization_gauge.data_points.push(OtlpDataPoint { attributes: attributes.clone(), value: OtlpValue::Fraction(utilization), });
+        inode_usage_gauge.data_points.push(OtlpDataPoint { attributes: with_state("used"), value: OtlpValue::Int(usage_entry.used_inodes), });
+        inode_usage_gauge.data_points.push(OtlpDataPoint { attributes: with_state("free"), value: OtlpValue::Int(usage_entry.available_inodes), });
+    }
+    let time_unix_nano = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|duration| duration.as_nanos()).unwrap_or(0);
+    otlp_metrics_request(&[usage_gauge, utilization_gauge, inode_usage_gauge], time_unix_nano)
+}
+
+/// Prints a low-level discovery document with the macros of the `vfs.fs.discovery` item of the
+/// Zabbix agent.
+fn print_zabbix_lld(usage_entries: &[UsageEntry])
+{
+    println!("{{\"data\":[");
+    for (i, usage_entry) in usage_entries.iter().enumerate() {
+        let separator = if i + 1 < usage_entries.len() { "," } else { "" };
+        let pairs = [
+            ("{#FSNAME}", Value::String(usage_entry.mount_point.clone())),
+            ("{#FSTYPE}", Value::String(usage_entry.file_system_type.clone())),
+            ("{#FSDEVICE}", Value::String(usage_entry.file_system.clone())),
+            ("{#FSOPTIONS}", Value::String(usage_entry.mount_options.clone())),
+        ];
+        println!("  {}{}", json_object(&pairs), separator);
+    }
+    println!("]}}");
+}
+
+/// Prints values of filesystem items as the `vfs.fs.size` item of the Zabbix agent or as the
+/// `vfs.fs.inode` item if inode information is printed, one value per line.
+fn print_zabbix_values(usage_entries: &[UsageEntry], zabbix_mode: ZabbixMode, opts: &Options)
+{
+    for usage_entry in usage_entries.iter() {
+        if opts.inode_flag {
+            println!("{}", zabbix_value(zabbix_mode, usage_entry.total_inodes, usage_entry.used_inodes, i128::from(usage_entry.available_inodes)));
+        } else {
+            println!("{}", zabbix_value(zabbix_mode, usage_entry.total_bytes, usage_entry.used_bytes, i128::from(usage_entry.available_bytes)));
+        }
+    }
+}
+
+/// Returns the state of a capacity according to the warning and critical thresholds.
+fn threshold_state(percent: u64, opts: &Options) -> PluginState
+{
+    if opts.critical_percent.map(|critical_percent| percent >= critical_percent).unwrap_or(false) {
+        PluginState::Critical
+    } else if opts.warning_percent.map(|warning_percent| percent >= warning_percent).unwrap_or(false) {
+        PluginState::Warning
+    } else {
+        PluginState::Ok
+    }
+}
+
+/// Prints a summary of a Nagios plugin with performance data of used space or inodes and returns
+/// the exit status of the plugin. The state is unknown if there were errors or no filesystems,
+/// unless a capacity reaches a threshold.
+fn print_check(usage_entries: &[UsageEntry], status: i32, opts: &Options) -> i32
+{
+    let mut state = PluginState::Ok;
+    let mut problems: Vec<String> = Vec::new();
+    let mut perfdatas: Vec<String> = Vec::new();
+    for usage_entry in usage_entries.iter() {
+        let percent = usage_entry.capacity(opts);
+        let entry_state = threshold_state(percent, opts);
+        if entry_state == PluginState::Critical {
+            state = PluginState::Critical;
+        } else if entry_state == PluginState::Warning && state != PluginState::Critical {
+            state = PluginState::Warning;
+        }
+        if entry_state != PluginState::Ok {
+            problems.push(format!("{} {}% used", usage_entry.mount_point, percent));
+        }
+        let (used, available, total, unit) = if opts.inode_flag {
+            (usage_entry.used_inodes, usage_entry.available_inodes, usage_entry.total_inodes, "")
+        } else {
+            (usage_entry.used_bytes, usage_entry.available_bytes.max(0) as u64, usage_entry.total_bytes, "B")
+        };
+        // Thresholds are relative to the sum of used and available space as capacities.
+        let threshold = |percent: u64| Rounding::Up.div((u128::from(used) + u128::from(available)) * u128::from(percent), 100) as u64;
+        perfdatas.push(perfdata(usage_entry.mount_point.as_str(), used, unit, opts.warning_percent.map(threshold), opts.critical_percent.map(threshold), total));
+    }
+    if state == PluginState::Ok && (status != 0 || usage_entries.is_empty()) {
+        state = PluginState::Unknown;
+    }
+    let summary = if !problems.is_empty() {
+        problems.join(", ")
+    } else if !usage_entries.is_empty() {
+        String::from("all filesystems below thresholds")
+    } else {
+        String::from("no filesystems")
+    };
+    if !perfdatas.is_empty() {
+        println!("DISK {} - {} | {}", state.name(), summary, perfdatas.join(" "));
+    } else {
+        println!("DISK {} - {}", state.name(), summary);
+    }
+    state.exit_status()
+}
+
+#[cfg(feature = "xml")]
+fn print_xml(usage_entries: &[UsageEntry], fields: &[Field], opts: &Options)
+{
+    println!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    println!("<filesystems>");
+    for usage_entry in usage_entries.iter() {
+        let pairs: Vec<(&str, Value)> = fields.iter().map(|field| (field.name(), field_raw_value(usage_entry, *field, opts))).collect();
+        println!("  {}", xml_empty_element("filesystem", pairs.as_slice()));
+    }
+    println!("</filesystems>");
+}
+
+fn read_paths<R: BufRead>(r: R, delimiter: u8, paths: &mut Vec<String>) -> i32
+{
+    for path in r.split(delimiter) {
+        match path {
+            Ok(path) => {
+                if !path.is_empty() {
+                    paths.push(String::from_utf8_lossy(path.as_slice()).into_owned());
+                }
+            },
+            Err(err) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+        }
+    }
+    0
+}
+
+fn parse_percent(s: &str) -> Option<u64>
+{
+    match s.strip_suffix('%').unwrap_or(s).parse::<u64>() {
+        Ok(percent) if percent <= 100 => Some(percent),
+        _                             => None,
+    }
+}
+
+/// Unescapes the `\t`, `\n` and `\\` escape sequences in a separator.
+fn unescape_separator(s: &str) -> String
+{
+    let mut t = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t')  => t.push('\t'),
+                Some('n')  => t.push('\n'),
+                Some('\\') => t.push('\\'),
+                Some(c) => {
+                    t.push('\\');
+                    t.push(c);
+                },
+                None       => t.push('\\'),
+            }
+        } else {
+            t.push(c);
+        }
+    }
+    t
+}
+
+fn set_block_size(opts: &mut Options, s: &str)
+{
+    match parse_size_unit(s) {
+        Some(size_unit) => opts.size_unit = Some(size_unit),
+        None => {
+            eprintln!("{} -- {:?}", gettext("invalid block size"), s);
+            exit(1);
+        },
+    }
+}
+
+/// Estimates the time until each filesystem is full by growth of used space. Growth is estimated
+/// from the samples of previous printings in watch mode. The current usage is added to the samples.
+fn estimate_full_in(usage_entries: &mut [UsageEntry], samples: &mut UsageSamples)
+{
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs_f64()).unwrap_or(0.0);
+    for usage_entry in usage_entries.iter_mut() {
+        samples.add(usage_entry.mount_point.as_str(), time, usage_entry.used_bytes as f64);
+        usage_entry.full_in = time_until_full(samples.samples(usage_entry.mount_point.as_str()), usage_entry.available_bytes);
+    }
+}
+
+/// Estimates the time until each filesystem is full from the records of the database of
+/// `--record` and the current usage.
+#[cfg(feature = "sqlite")]
+fn estimate_full_in_from_records(usage_entries: &mut [UsageEntry], record_path: &Path) -> i32
+{
+    let database = match history::open_sample_database(record_path) {
+        Ok(database) => database,
+        Err(err) => {
+            eprintln!("{}: {}", record_path.to_string_lossy(), err);
+            return 1;
+        },
+    };
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs_f64()).unwrap_or(0.0);
+    for usage_entry in usage_entries.iter_mut() {
+        let recorded_samples = match &database {
+            Some(database) => history::recorded_samples(database, usage_entry.mount_point.as_str()),
+            None           => Ok(Vec::new()),
+        };
+        let mut entry_samples = match recorded_samples {
+            Ok(recorded_samples) => recorded_samples,
+            Err(err) => {
+                eprintln!("{}: {}", record_path.to_string_lossy(), err);
+                return 1;
+            },
+        };
+        entry_samples.push((time, usage_entry.used_bytes as f64));
+        usage_entry.full_in = time_until_full(entry_samples.as_slice(), usage_entry.available_bytes);
+    }
+    0
+}
+
+/// Gets usage of filesystems of the paths or of all filesystems if there are no paths. If
+/// `is_streamed` is `true`, the entries are printed as JSON lines as soon as usage is got. The
+/// entries and the exit status are returned.
+fn get_usage_entries(paths: Option<&[String]>, fields: &[Field], is_streamed: bool, samples: &mut UsageSamples, opts: &Options) -> (Vec<UsageEntry>, i32)
+{
+    let mut status = 0;
+    let mountinfo_entries = match &opts.mountinfo {
+        Some(mountinfo) => read_mountinfo(mountinfo).unwrap_or_default(),
+        None            => Vec::new(),
+    };
+    // Filesystems of paths from a file are collapsed because such lists are usually long, unless
+    // the paths are printed.
+    let is_collapsed = opts.files_from.is_some() && !opts.all_flag && !fields.contains(&Field::File);
+    let mut mount_points: HashSet<PathBuf> = HashSet::new();
+    let mut usage_entries: Vec<UsageEntry> = Vec::new();
+    if let Some(paths) = paths {
+        for path in paths.iter() {
+            match fs::metadata(rooted_path(path, opts)) {
+                Ok(metadata) => {
+                    let is_device = metadata.file_type().is_block_device();
+                    let mount_entry = if is_device {
+                        find_device_mount(metadata.rdev(), opts.mtab.as_path(), mountinfo_entries.as_slice())
+                    } else {
+                        // Relative paths and paths with symbolic links are resolved because mount
+                        // points are matched by path prefixes.
+                        match fs::canonicalize(rooted_path(path, opts)) {
+                            Ok(canonical_path) => find_mount(unrooted_path(canonical_path, opts), opts.mtab.as_path()),
+                            Err(err) => {
+                                eprintln!("{}: {}", path, err);
+                                status = 1;
+                                continue;
+                            },
+                        }
+                    };
+                    match mount_entry {
+                        Ok(Some(mount_entry)) if opts.target_only_flag => println!("{}", mount_entry.file.as_path().to_string_lossy()),
+                        Ok(Some(mount_entry)) if !is_selected_mount_entry(&mount_entry, opts) => (),
+                        Ok(Some(mount_entry)) if opts.bind_mount_mode == BindMountMode::Hide && is_bind_mount_entry(&mount_entry, mountinfo_entries.as_slice()) => (),
+                        Ok(Some(mount_entry)) if is_collapsed && !mount_points.insert(mount_entry.file.clone()) => (),
+                        Ok(Some(mount_entry)) => {
+                            match mount_entry_to_usage_entry(&mount_entry, if is_device { None } else { Some(Path::new(path)) }, mountinfo_entries.as_slice(), opts, true) {
+                                Some(Some(mut usage_entry)) => {
+                                    usage_entry.file = Some(path.clone());
+                                    add_usage_entry(&mut usage_entries, usage_entry, fields, is_streamed, opts);
+                                },
+                                Some(None)              => (),
+                                None                    => status = 1,
+                            }
+                        },
+                        Ok(None) if is_device => {
+                            eprintln!("{}: {}", path, gettext("Device isn't mounted"));
+                            status = 1;
+                        },
+                        Ok(None) => {
+                            eprintln!("{}: {}", path, gettext("Can't find mount entry"));
+                            status = 1;
+                        },
+                        Err(err) => {
+                            eprintln!("{}", err);
+                            status = 1;
+                        },
+                    }
                 },
                 Err(err) => {
                     eprintln!("{}: {}", path, err);
@@ -280,13 +2310,21 @@ fn main()
             }
         }
     } else {
-        match get_mounts() {
+        match get_mounts(opts.mtab.as_path()) {
             Ok(mount_entries) => {
+                let mut mount_entries: Vec<MountEntry> = mount_entries.into_iter().filter(|mount_entry| is_selected_mount_entry(mount_entry, opts)).collect();
+                if opts.bind_mount_mode == BindMountMode::Hide {
+                    mount_entries.retain(|mount_entry| !is_bind_mount_entry(mount_entry, mountinfo_entries.as_slice()));
+                }
+                if !opts.all_flag {
+                    mount_entries.retain(|mount_entry| !opts.pseudo_types.contains(&mount_entry.vfstype));
+                    mount_entries = dedup_mounts(mount_entries, opts);
+                }
                 for mount_entry in &mount_entries {
-                    match mount_entry_to_format_entry(mount_entry, &opts, false) {
-                        Some(Some(format_entry)) => format_entries.push(format_entry),
-                        Some(None)               => (),
-                        None                     => status = 1,
+                    match mount_entry_to_usage_entry(mount_entry, None, mountinfo_entries.as_slice(), opts, opts.all_flag) {
+                        Some(Some(usage_entry)) => add_usage_entry(&mut usage_entries, usage_entry, fields, is_streamed, opts),
+                        Some(None)              => (),
+                        None                    => status = 1,
                     }
                 }
             },
@@ -296,9 +2334,814 @@ fn main()
             },
         }
     }
-    if format_entries.len() > 1 {
-        let format_max_lens = calculate_format_max_lens(format_entries.as_slice());
-        print_format_entries(format_entries.as_slice(), &format_max_lens);
+    if fields.contains(&Field::FullIn) {
+        estimate_full_in(usage_entries.as_mut_slice(), samples);
+        // The records already contain the samples of watch mode, so the estimates are replaced.
+        #[cfg(feature = "sqlite")]
+        if let Some(record_path) = &opts.record_path {
+            status |= estimate_full_in_from_records(usage_entries.as_mut_slice(), record_path.as_path());
+        }
+    }
+    if opts.overlay_layers_flag {
+        let mut new_usage_entries: Vec<UsageEntry> = Vec::new();
+        for usage_entry in usage_entries {
+            let (layer_entries, layer_status) = if usage_entry.file_system_type == "overlay" {
+                overlay_layer_usage_entries(&usage_entry, mountinfo_entries.as_slice(), opts)
+            } else {
+                (Vec::new(), 0)
+            };
+            status |= layer_status;
+            new_usage_entries.push(usage_entry);
+            new_usage_entries.extend(layer_entries);
+        }
+        usage_entries = new_usage_entries;
+    }
+    if let Some(over_percent) = opts.over_percent {
+        usage_entries.retain(|usage_entry| usage_entry.capacity(opts) >= over_percent);
+    }
+    if let Some(top_count) = opts.top_count {
+        usage_entries.sort_by_key(|usage_entry| Reverse(usage_entry.capacity(opts)));
+        usage_entries.truncate(top_count);
+    }
+    if let Some(sort_field) = opts.sort_field {
+        usage_entries.sort_by(|usage_entry1, usage_entry2| compare_usage_entries(usage_entry1, usage_entry2, sort_field, opts));
+    }
+    if opts.hierarchy_flag {
+        usage_entries = hierarchy_order(usage_entries, opts.tree_flag, opts.ascii_flag);
+    }
+    if opts.reverse_flag {
+        usage_entries.reverse();
+    }
+    (usage_entries, status)
+}
+
+/// A state which is kept between printings in watch mode.
+struct PrintState
+{
+    samples: UsageSamples,
+    /// The mount points of filesystems which are over the alert threshold.
+    alerted_mount_points: HashSet<String>,
+    /// The logged threshold states of filesystems.
+    log_states: HashMap<String, PluginState>,
+}
+
+impl PrintState
+{
+    fn new() -> PrintState
+    {
+        PrintState { samples: UsageSamples::new(), alerted_mount_points: HashSet::new(), log_states: HashMap::new(), }
+    }
+}
+
+/// Returns the filesystems which change their threshold states with the new states and updates
+/// the states.
+fn threshold_crossings<'a>(usage_entries: &'a [UsageEntry], states: &mut HashMap<String, PluginState>, opts: &Options) -> Vec<(&'a UsageEntry, PluginState)>
+{
+    let mut crossings: Vec<(&'a UsageEntry, PluginState)> = Vec::new();
+    for usage_entry in usage_entries.iter() {
+        let state = threshold_state(usage_entry.capacity(opts), opts);
+        let old_state = states.insert(usage_entry.mount_point.clone(), state).unwrap_or(PluginState::Ok);
+        if state != old_state {
+            crossings.push((usage_entry, state));
+        }
+    }
+    crossings
+}
+
+/// Logs a filesystem which changes its threshold state. A filesystem which reaches the warning or
+/// critical threshold is logged with the warning or critical priority, and a filesystem which goes
+/// back below the thresholds is logged with the notice priority.
+fn log_threshold_crossing(usage_entry: &UsageEntry, state: PluginState, log_target: LogTarget, opts: &Options) -> Result<()>
+{
+    let percent = usage_entry.capacity(opts);
+    let priority = match state {
+        PluginState::Critical => libc::LOG_CRIT,
+        PluginState::Warning  => libc::LOG_WARNING,
+        _                     => libc::LOG_NOTICE,
+    };
+    let message = format!("{} {}: {}% used", state.name(), usage_entry.mount_point, percent);
+    let fields = [
+        ("MNTDF_STATE", String::from(state.name())),
+        ("MNTDF_SOURCE", usage_entry.file_system.clone()),
+        ("MNTDF_FSTYPE", usage_entry.file_system_type.clone()),
+        ("MNTDF_TARGET", usage_entry.mount_point.clone()),
+        ("MNTDF_PCENT", format!("{}", percent)),
+        ("MNTDF_AVAIL", format!("{}", usage_entry.available_bytes)),
+    ];
+    log_message(log_target, priority, message.as_str(), &fields)
+}
+
+/// Logs each filesystem which changes its threshold state.
+fn log_thresholds(usage_entries: &[UsageEntry], log_target: LogTarget, log_states: &mut HashMap<String, PluginState>, opts: &Options) -> i32
+{
+    for (usage_entry, state) in threshold_crossings(usage_entries, log_states, opts) {
+        if let Err(err) = log_threshold_crossing(usage_entry, state, log_target, opts) {
+            eprintln!("{}", err);
+            return 1;
+        }
+    }
+    0
+}
+
+/// Runs the alert command for each filesystem which crosses the alert threshold. Usage of the
+/// filesystem is passed in environment variables. A filesystem which stays over the threshold
+/// doesn't run the command again in watch mode.
+fn run_alerts(usage_entries: &[UsageEntry], alert_percent: u64, alert_command: &str, alerted_mount_points: &mut HashSet<String>, opts: &Options) -> i32
+{
+    let mut status = 0;
+    for usage_entry in usage_entries.iter() {
+        let percent = usage_entry.capacity(opts);
+        if percent < alert_percent {
+            alerted_mount_points.remove(&usage_entry.mount_point);
+            continue;
+        }
+        if !alerted_mount_points.insert(usage_entry.mount_point.clone()) {
+            continue;
+        }
+        let _ = stdout().flush();
+        let res = Command::new("sh").arg("-c").arg(alert_command)
+            .env("MNTDF_SOURCE", usage_entry.file_system.as_str())
+            .env("MNTDF_FSTYPE", usage_entry.file_system_type.as_str())
+            .env("MNTDF_TARGET", usage_entry.mount_point.as_str())
+            .env("MNTDF_SIZE", format!("{}", usage_entry.total_bytes))
+            .env("MNTDF_USED", format!("{}", usage_entry.used_bytes))
+            .env("MNTDF_AVAIL", format!("{}", usage_entry.available_bytes))
+            .env("MNTDF_PCENT", format!("{}", percent))
+            .env("MNTDF_THRESHOLD", format!("{}", alert_percent))
+            .status();
+        match res {
+            Ok(exit_status) if exit_status.success() => (),
+            Ok(exit_status) => {
+                eprintln!("{}: {}", alert_command, exit_status);
+                status = 1;
+            },
+            Err(err) => {
+                eprintln!("{}: {}", alert_command, err);
+                status = 1;
+            },
+        }
+    }
+    status
+}
+
+/// Gets usage of filesystems of the paths or of all filesystems if there are no paths, and prints
+/// it in the output format. The status is the exit status of previous operations and the new exit
+/// status is returned.
+fn print_usage(paths: Option<&[String]>, fields: &[Field], terminal_width: Option<usize>, status: i32, state: &mut PrintState, opts: &Options) -> i32
+{
+    // JSON lines are printed as soon as usage is got unless all entries are needed to order them.
+    let is_streamed = opts.output_format == OutputFormat::JsonLines && !opts.problems_only_flag && opts.summary_key.is_none() && !opts.overlay_layers_flag && opts.top_count.is_none() && opts.sort_field.is_none() && !opts.hierarchy_flag && !opts.reverse_flag && !fields.contains(&Field::FullIn);
+    let (mut usage_entries, entries_status) = get_usage_entries(paths, fields, is_streamed, &mut state.samples, opts);
+    let mut status = status | entries_status;
+    if let Some(statsd_addr) = &opts.statsd_addr {
+        if let Err(err) = send_statsd(statsd_addr.as_str(), usage_entries_to_statsd(usage_entries.as_slice(), opts).as_slice()) {
+            eprintln!("{}: {}", statsd_addr, err);
+            status = 1;
+        }
+    }
+    #[cfg(feature = "otlp")]
+    if let Some(otlp_endpoint) = &opts.otlp_endpoint {
+        if let Err(err) = send_otlp(otlp_endpoint.as_str(), usage_entries_to_otlp(usage_entries.as_slice()).as_str()) {
+            eprintln!("{}: {}", otlp_endpoint, err);
+            status = 1;
+        }
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(record_path) = &opts.record_path {
+        if let Err(err) = history::record_usage(record_path.as_path(), usage_entries.as_slice()) {
+            eprintln!("{}: {}", record_path.to_string_lossy(), err);
+            status = 1;
+        }
+    }
+    if let Some(log_target) = opts.log_target {
+        status |= log_thresholds(usage_entries.as_slice(), log_target, &mut state.log_states, opts);
+    }
+    if let (Some(alert_percent), Some(alert_command)) = (opts.alert_percent, &opts.alert_command) {
+        status |= run_alerts(usage_entries.as_slice(), alert_percent, alert_command.as_str(), &mut state.alerted_mount_points, opts);
+    }
+    // Filesystems are filtered after logging and alerts, so that their states are still updated.
+    if opts.problems_only_flag {
+        usage_entries.retain(|usage_entry| usage_entry.is_problem(opts));
+        // Nothing is printed in any format if there are no problems.
+        if usage_entries.is_empty() {
+            return status;
+        }
+    }
+    if let Some(summary_key) = opts.summary_key {
+        usage_entries = summary_usage_entries(usage_entries.as_slice(), summary_key);
+    }
+    let printed_count = if is_streamed { usage_entries.len() } else { 0 };
+    if opts.total_flag && !usage_entries.is_empty() {
+        let total_entry = total_usage_entry(usage_entries.as_slice());
+        usage_entries.push(total_entry);
+    }
+    match opts.output_format {
+        OutputFormat::Table if !usage_entries.is_empty() => {
+            let mut format_entries: Vec<FormatEntry> = Vec::new();
+            if !opts.no_header_flag {
+                format_entries.push(header_format_entry(fields, opts));
+            }
+            for usage_entry in &usage_entries {
+                format_entries.push(usage_entry_to_format_entry(usage_entry, fields, opts));
+            }
+            let mut format_max_lens = calculate_format_max_lens(format_entries.as_slice(), fields);
+            let mut truncated_fields = opts.truncated_fields.clone();
+            let is_first_wrapped = opts.wrap_flag && fields.first() == Some(&Field::Source);
+            if is_first_wrapped {
+                format_max_lens[0] = min(format_max_lens[0], SOURCE_WRAP_WIDTH);
+                truncated_fields.retain(|field| *field != Field::Source);
+            }
+            // Values separated by a separator aren't padded, so they aren't truncated either.
+            if let Some(terminal_width) = terminal_width.filter(|_| opts.separator.is_none()) {
+                truncate_format_entries(format_entries.as_mut_slice(), fields, format_max_lens.as_mut_slice(), terminal_width, truncated_fields.as_slice(), if opts.ascii_flag { ASCII_ELLIPSIS } else { ELLIPSIS });
+            }
+            match &opts.separator {
+                Some(separator) => print_separated_format_entries(format_entries.as_slice(), separator.as_str()),
+                None            => print_format_entries(format_entries.as_slice(), fields, format_max_lens.as_slice(), is_first_wrapped),
+            }
+        },
+        OutputFormat::Table     => (),
+        OutputFormat::Markdown if !usage_entries.is_empty() => print_markdown(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Markdown  => (),
+        OutputFormat::Null if !usage_entries.is_empty() => print_null_separated(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Null      => (),
+        OutputFormat::Records   => print_records(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Template  => print_template(usage_entries.as_slice(), opts),
+        OutputFormat::Html      => print_html(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Json      => print_json(usage_entries.as_slice(), fields, opts),
+        OutputFormat::JsonLines => print_json_lines(&usage_entries[printed_count..], fields, opts),
+        OutputFormat::Csv       => print_csv(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Tsv       => print_tsv(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Yaml      => print_yaml(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Shell     => print_shell(usage_entries.as_slice(), fields, opts),
+        OutputFormat::Prometheus => print!("{}", prometheus_text(usage_entries.as_slice())),
+        OutputFormat::Influx    => print_influx(usage_entries.as_slice()),
+        OutputFormat::ZabbixLld => print_zabbix_lld(usage_entries.as_slice()),
+        OutputFormat::ZabbixValue(zabbix_mode) => print_zabbix_values(usage_entries.as_slice(), zabbix_mode, opts),
+        OutputFormat::Check     => status = print_check(usage_entries.as_slice(), status, opts),
+        #[cfg(feature = "xml")]
+        OutputFormat::Xml       => print_xml(usage_entries.as_slice(), fields, opts),
+    }
+    status
+}
+
+/// Returns the current local time as a string, for example `2023-01-01 12:00:00`.
+fn local_time_string() -> String
+{
+    format_local_time(unsafe { libc::time(std::ptr::null_mut()) })
+}
+
+/// Formats a time in seconds since the Unix epoch as a local time.
+fn format_local_time(time: libc::time_t) -> String
+{
+    let mut tm: MaybeUninit<libc::tm> = MaybeUninit::uninit();
+    if unsafe { libc::localtime_r(&time, tm.as_mut_ptr()) }.is_null() {
+        return String::new();
+    }
+    let mut buf = [0 as libc::c_char; 64];
+    let format_cstring = CString::new("%Y-%m-%d %H:%M:%S").unwrap();
+    let len = unsafe { libc::strftime(buf.as_mut_ptr(), buf.len(), format_cstring.as_ptr(), tm.as_ptr()) };
+    String::from_utf8_lossy(&buf[..len].iter().map(|c| *c as u8).collect::<Vec<u8>>()).into_owned()
+}
+
+/// Prints usage of filesystems periodically. The screen is cleared before each printing on a
+/// terminal, otherwise blocks of output are appended with timestamps.
+fn watch(paths: Option<&[String]>, fields: &[Field], watch_interval: Duration, opts: &Options) -> !
+{
+    let mut state = PrintState::new();
+    loop {
+        let terminal_width = terminal_size().map(|(cols, _)| cols);
+        if terminal_width.is_some() {
+            print!("\x1b[H\x1b[2J");
+        }
+        println!("{}", local_time_string());
+        println!();
+        print_usage(paths, fields, terminal_width, 0, &mut state, opts);
+        if terminal_width.is_none() {
+            println!();
+        }
+        let _ = stdout().flush();
+        thread::sleep(watch_interval);
+    }
+}
+
+fn main()
+{
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|arg| arg == "check").unwrap_or(false) {
+        exit(check::check_main(args[0].as_str(), &args[1..]));
+    }
+    if args.get(1).map(|arg| arg == "daemon").unwrap_or(false) {
+        exit(daemon::daemon_main(args[0].as_str(), &args[1..]));
+    }
+    if args.get(1).map(|arg| arg == "snapshot").unwrap_or(false) {
+        exit(snapshot::snapshot_main(args[0].as_str(), &args[1..]));
+    }
+    if args.get(1).map(|arg| arg == "diff").unwrap_or(false) {
+        exit(snapshot::diff_main(args[0].as_str(), &args[1..]));
+    }
+    #[cfg(feature = "sqlite")]
+    if args.get(1).map(|arg| arg == "history").unwrap_or(false) {
+        exit(history::history_main(args[0].as_str(), &args[1..]));
+    }
+    let mut opt_parser = Parser::new(&args, OPTIONS);
+    let mut opts = Options::new();
+    if let Some(path) = config_path("pseudo-fs") {
+        match read_config_list(path.as_path()) {
+            Ok(Some(names)) => opts.pseudo_types = names.into_iter().collect(),
+            Ok(None)        => (),
+            Err(err) => {
+                eprintln!("{}: {}", path.to_string_lossy(), err);
+                exit(1);
+            },
+        }
+    }
+    if let Some(path) = config_path("config.toml") {
+        match read_config_toml(path.as_path()) {
+            Ok(Some(document)) => {
+                // A relative path of a theme is relative to the configuration directory.
+                match document.table("").and_then(|table| table.get("theme")) {
+                    Some(TomlValue::String(theme_name)) => {
+                        let theme_path = path.parent().map(|dir| dir.join(theme_name)).unwrap_or_else(|| PathBuf::from(theme_name));
+                        match Theme::read(theme_path.as_path()) {
+                            Ok(theme) => opts.theme = theme,
+                            Err(err) => {
+                                eprintln!("{}: {}", theme_path.to_string_lossy(), err);
+                                exit(1);
+                            },
+                        }
+                    },
+                    Some(_) => {
+                        eprintln!("{}: theme: {}", path.to_string_lossy(), gettext("expected string"));
+                        exit(1);
+                    },
+                    None => (),
+                }
+            },
+            Ok(None) => (),
+            Err(err) => {
+                eprintln!("{}: {}", path.to_string_lossy(), err);
+                exit(1);
+            },
+        }
+    }
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt(Short('a'), _))) => opts.all_flag = true,
+            Some(Ok(Opt(Short('b'), _))) => opts.size_unit = Some(SizeUnit::Blocks(1)),
+            Some(Ok(Opt(Short('B'), Some(opt_arg)))) => set_block_size(&mut opts, opt_arg.as_str()),
+            Some(Ok(Opt(Short('H'), _))) => opts.size_unit = Some(SizeUnit::Human(SI_BASE)),
+            Some(Ok(Opt(Long("group-digits"), _))) => opts.group_digits_flag = true,
+            Some(Ok(Opt(Long("hierarchy"), _))) => opts.hierarchy_flag = true,
+            Some(Ok(Opt(Long("tree"), _))) => {
+                opts.hierarchy_flag = true;
+                opts.tree_flag = true;
+            },
+            Some(Ok(Opt(Short('h'), _))) => opts.size_unit = Some(SizeUnit::Human(BINARY_BASE)),
+            Some(Ok(Opt(Short('i'), _))) => opts.inode_flag = true,
+            Some(Ok(Opt(Short('k'), _))) => {
+                opts.kilo_flag = true;
+                opts.size_unit = None;
+            },
+            Some(Ok(Opt(Short('l'), _))) => opts.local_flag = true,
+            Some(Ok(Opt(Long("pid"), Some(opt_arg)))) => {
+                match opt_arg.parse::<u32>() {
+                    Ok(pid) if pid != 0 => {
+                        let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+                        if !proc_dir.exists() {
+                            eprintln!("{}: {}", pid, gettext("No such process"));
+                            exit(1);
+                        }
+                        opts.mtab = proc_dir.join("mounts");
+                        opts.mountinfo = Some(proc_dir.join("mountinfo"));
+                        opts.root = Some(proc_dir.join("root"));
+                    },
+                    _ => {
+                        eprintln!("{} -- {:?}", gettext("invalid PID"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("overlay-layers"), _))) => opts.overlay_layers_flag = true,
+            Some(Ok(Opt(Short('P'), _))) => opts.compat = Compat::Posix,
+            Some(Ok(Opt(Short('T'), _))) => opts.type_flag = true,
+            Some(Ok(Opt(Short('o'), _))) => opts.options_flag = true,
+            Some(Ok(Opt(Short('t'), Some(opt_arg)))) => opts.include_types.push(opt_arg),
+            Some(Ok(Opt(Short('v'), _))) => opts.verbose_flag = true,
+            Some(Ok(Opt(Long("wide"), _))) => opts.wide_flag = true,
+            Some(Ok(Opt(Long("wrap"), _))) => opts.wrap_flag = true,
+            Some(Ok(Opt(Long("separator"), Some(opt_arg)))) => opts.separator = Some(unescape_separator(opt_arg.as_str())),
+            Some(Ok(Opt(Long("statsd"), Some(opt_arg)))) => opts.statsd_addr = Some(opt_arg),
+            Some(Ok(Opt(Long("watch"), opt_arg))) => {
+                match parse_duration(opt_arg.as_deref().unwrap_or("2")) {
+                    Some(interval) if !interval.is_zero() => opts.watch_interval = Some(interval),
+                    _ => {
+                        eprintln!("{} -- {:?}", gettext("invalid interval"), opt_arg.unwrap());
+                        exit(1);
+                    },
+                }
+            },
+            #[cfg(feature = "otlp")]
+            Some(Ok(Opt(Long("otlp"), Some(opt_arg)))) => opts.otlp_endpoint = Some(opt_arg),
+            #[cfg(feature = "sqlite")]
+            Some(Ok(Opt(Long("record"), opt_arg))) => {
+                match opt_arg.map(PathBuf::from).or_else(history::default_database_path) {
+                    Some(record_path) => opts.record_path = Some(record_path),
+                    None => {
+                        eprintln!("{}", gettext("Can't determine the data directory"));
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Short('x'), Some(opt_arg)))) => {
+                opts.exclude_types.insert(opt_arg);
+            },
+            Some(Ok(Opt(Long("ascii"), _))) => opts.ascii_flag = true,
+            Some(Ok(Opt(Long("add-pseudo-fs"), Some(opt_arg)))) => {
+                opts.pseudo_types.insert(opt_arg);
+            },
+            Some(Ok(Opt(Long("pseudo-fs"), Some(opt_arg)))) => {
+                opts.pseudo_types = opt_arg.split(',').filter(|name| !name.is_empty()).map(String::from).collect();
+            },
+            Some(Ok(Opt(Long("bind-mounts"), Some(opt_arg)))) => {
+                match opt_arg.as_str() {
+                    "show"     => opts.bind_mount_mode = BindMountMode::Show,
+                    "hide"     => opts.bind_mount_mode = BindMountMode::Hide,
+                    "annotate" => opts.bind_mount_mode = BindMountMode::Annotate,
+                    _ => {
+                        eprintln!("{} -- {:?}", gettext("invalid bind mount mode"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("compat"), Some(opt_arg)))) => {
+                match Compat::from_name(opt_arg.as_str()) {
+                    Some(compat) => opts.compat = compat,
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid compatibility mode"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("exclude-mountpoint"), Some(opt_arg)))) => opts.exclude_mount_points.push(PathBuf::from(opt_arg)),
+            Some(Ok(Opt(Long("files-from"), Some(opt_arg)))) => opts.files_from = Some((opt_arg, b'\n')),
+            Some(Ok(Opt(Long("files0-from"), Some(opt_arg)))) => opts.files_from = Some((opt_arg, 0)),
+            Some(Ok(Opt(Long("help"), _))) => {
+                print_help(args.first().map(|s| s.as_str()).unwrap_or("mntdf"));
+                exit(0);
+            },
+            Some(Ok(Opt(Long("include"), Some(opt_arg)))) => opts.include_mount_point_globs.push(opt_arg),
+            Some(Ok(Opt(Long("mark-read-only"), _))) => opts.mark_read_only_flag = true,
+            Some(Ok(Opt(Long("mtab"), Some(opt_arg)))) => {
+                opts.mtab = PathBuf::from(opt_arg);
+                // The mountinfo file describes the current mount table, so it isn't used with an
+                // alternate mount table.
+                opts.mountinfo = None;
+                opts.root = None;
+            },
+            Some(Ok(Opt(Long("namespace"), Some(opt_arg)))) => opts.namespace = Some(PathBuf::from(opt_arg)),
+            Some(Ok(Opt(Long("markdown"), _))) => opts.output_format = OutputFormat::Markdown,
+            Some(Ok(Opt(Long("html"), _))) => opts.output_format = OutputFormat::Html,
+            Some(Ok(Opt(Long("html-usage-style"), _))) => opts.html_usage_style_flag = true,
+            Some(Ok(Opt(Short('0'), _))) => opts.output_format = OutputFormat::Null,
+            Some(Ok(Opt(Long("shell"), _))) => opts.output_format = OutputFormat::Shell,
+            Some(Ok(Opt(Long("problems-only"), _))) => opts.problems_only_flag = true,
+            Some(Ok(Opt(Long("prometheus"), _))) => opts.output_format = OutputFormat::Prometheus,
+            Some(Ok(Opt(Long("influx"), _))) => opts.output_format = OutputFormat::Influx,
+            Some(Ok(Opt(Long("zabbix-lld"), _))) => opts.output_format = OutputFormat::ZabbixLld,
+            Some(Ok(Opt(Long("zabbix-value"), Some(opt_arg)))) => {
+                match ZabbixMode::from_name(opt_arg.as_str()) {
+                    Some(zabbix_mode) => opts.output_format = OutputFormat::ZabbixValue(zabbix_mode),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid Zabbix mode"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("format"), Some(opt_arg)))) => {
+                match parse_template(opt_arg.as_str()) {
+                    Ok(items) => {
+                        opts.template = items;
+                        opts.output_format = OutputFormat::Template;
+                    },
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("records"), _))) => opts.output_format = OutputFormat::Records,
+            Some(Ok(Opt(Long("bar"), opt_arg))) => {
+                match opt_arg.map(|opt_arg| opt_arg.parse::<usize>()) {
+                    Some(Ok(width)) if width > 0 && width <= MAX_BAR_WIDTH => opts.bar_width = width,
+                    None => (),
+                    _ => {
+                        eprintln!("{}", gettext("invalid bar width"));
+                        exit(1);
+                    },
+                }
+                opts.bar_flag = true;
+            },
+            Some(Ok(Opt(Long("full-in"), opt_arg))) => {
+                match opt_arg.as_deref().map(parse_duration) {
+                    Some(Some(horizon)) => opts.full_horizon = horizon,
+                    None => (),
+                    _ => {
+                        eprintln!("{} -- {:?}", gettext("invalid interval"), opt_arg.unwrap_or_default());
+                        exit(1);
+                    },
+                }
+                opts.full_in_flag = true;
+            },
+            Some(Ok(Opt(Long("color"), opt_arg))) => {
+                match opt_arg.as_deref().map(ColorMode::from_name).unwrap_or(Some(ColorMode::Always)) {
+                    Some(color_mode) => opts.color_flag = color_mode.is_colored(),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid color mode"), opt_arg.unwrap());
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("csv"), _))) => opts.output_format = OutputFormat::Csv,
+            Some(Ok(Opt(Long("tsv"), _))) => opts.output_format = OutputFormat::Tsv,
+            Some(Ok(Opt(Long("yaml"), _))) => opts.output_format = OutputFormat::Yaml,
+            #[cfg(feature = "xml")]
+            Some(Ok(Opt(Long("xml"), _))) => opts.output_format = OutputFormat::Xml,
+            Some(Ok(Opt(Long("json"), _))) => opts.output_format = OutputFormat::Json,
+            Some(Ok(Opt(Long("jsonl"), _))) => opts.output_format = OutputFormat::JsonLines,
+            Some(Ok(Opt(Long("no-header"), _))) => opts.no_header_flag = true,
+            Some(Ok(Opt(Long("no-pager"), _))) => opts.pager_flag = false,
+            Some(Ok(Opt(Long("no-truncate"), _))) => opts.truncated_fields.clear(),
+            Some(Ok(Opt(Long("truncate"), Some(opt_arg)))) => {
+                match parse_fields(opt_arg.as_str(), "truncate") {
+                    Ok(fields) => opts.truncated_fields = fields,
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("output"), Some(opt_arg)))) => {
+                match parse_fields(opt_arg.as_str(), "output") {
+                    Ok(fields) => opts.output_fields = Some(fields),
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("check"), _))) => opts.output_format = OutputFormat::Check,
+            Some(Ok(Opt(Short('w'), Some(opt_arg)))) => {
+                match parse_percent(opt_arg.as_str()) {
+                    Some(percent) => opts.warning_percent = Some(percent),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid percentage"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Short('c'), Some(opt_arg)))) => {
+                match parse_percent(opt_arg.as_str()) {
+                    Some(percent) => opts.critical_percent = Some(percent),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid percentage"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("log"), Some(opt_arg)))) => {
+                match LogTarget::from_name(opt_arg.as_str()) {
+                    Some(log_target) => opts.log_target = Some(log_target),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid log target"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("on-over"), Some(opt_arg)))) => {
+                match parse_percent(opt_arg.as_str()) {
+                    Some(percent) => opts.alert_percent = Some(percent),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid percentage"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("exec-alert"), Some(opt_arg)))) => opts.alert_command = Some(opt_arg),
+            Some(Ok(Opt(Long("over"), Some(opt_arg)))) => {
+                match parse_percent(opt_arg.as_str()) {
+                    Some(percent) => opts.over_percent = Some(percent),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid percentage"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("precision"), Some(opt_arg)))) => {
+                match opt_arg.parse::<usize>() {
+                    Ok(precision) if precision <= MAX_PRECISION => opts.precision = Some(precision),
+                    _ => {
+                        eprintln!("{} -- {:?}", gettext("invalid precision"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Short('q'), _))) => opts.quiet_flag = true,
+            Some(Ok(Opt(Long("reverse"), _))) => opts.reverse_flag = true,
+            Some(Ok(Opt(Long("root"), Some(opt_arg)))) => {
+                let root = PathBuf::from(opt_arg);
+                match ["proc/mounts", "etc/mtab"].iter().map(|name| root.join(name)).find(|mtab| mtab.exists()) {
+                    Some(mtab) => opts.mtab = mtab,
+                    None => {
+                        eprintln!("{}: {}", root.to_string_lossy(), gettext("Can't find mount table"));
+                        exit(1);
+                    },
+                }
+                opts.mountinfo = None;
+                opts.root = Some(root);
+            },
+            Some(Ok(Opt(Long("rounding"), Some(opt_arg)))) => {
+                match Rounding::from_name(opt_arg.as_str()) {
+                    Some(rounding) => opts.rounding = Some(rounding),
+                    None => {
+                        eprintln!("{} -- {:?}", gettext("invalid rounding mode"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("sort"), Some(opt_arg)))) => {
+                match Field::from_name(opt_arg.as_str()) {
+                    Some(field) => opts.sort_field = Some(field),
+                    None => {
+                        eprintln!("{}", gettext_fmt("option {}: field {} unknown", &["--sort", format!("{:?}", opt_arg).as_str()]));
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("source-regex"), Some(opt_arg)))) => {
+                match Regex::new(opt_arg.as_str()) {
+                    Ok(regex) => opts.source_regex = Some(regex),
+                    Err(err) => {
+                        eprintln!("{}: {}", opt_arg, err);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("stdin"), _))) => opts.stdin_flag = true,
+            Some(Ok(Opt(Long("summary"), Some(opt_arg)))) => {
+                match opt_arg.as_str() {
+                    "fstype" => opts.summary_key = Some(SummaryKey::FsType),
+                    _ => {
+                        eprintln!("{} -- {:?}", gettext("invalid summary key"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("sync"), _))) => opts.sync_flag = true,
+            Some(Ok(Opt(Long("target-only"), _))) => opts.target_only_flag = true,
+            Some(Ok(Opt(Long("top"), Some(opt_arg)))) => {
+                match opt_arg.parse::<usize>() {
+                    Ok(count) => opts.top_count = Some(count),
+                    Err(_) => {
+                        eprintln!("{} -- {:?}", gettext("invalid number"), opt_arg);
+                        exit(1);
+                    },
+                }
+            },
+            Some(Ok(Opt(Long("total"), _))) => opts.total_flag = true,
+            Some(Ok(Opt(Long("version"), _))) => {
+                print_version();
+                exit(0);
+            },
+            Some(Ok(Opt(name, _))) => {
+                eprintln!("{} -- {:?}", gettext("unknown option"), name);
+                exit(1);
+            },
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                exit(1);
+            },
+            None => break,
+        }
+    }
+    if opts.size_unit.is_none() && !opts.kilo_flag {
+        opts.size_unit = env_size_unit();
+    }
+    if opts.output_fields.is_some() && opts.inode_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--output", "-i"]));
+        exit(1);
+    }
+    if opts.output_fields.is_some() && opts.type_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--output", "-T"]));
+        exit(1);
+    }
+    if opts.output_fields.is_some() && opts.options_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--output", "-o"]));
+        exit(1);
+    }
+    if opts.output_fields.is_some() && opts.output_format == OutputFormat::Template {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--output", "--format"]));
+        exit(1);
+    }
+    if opts.output_fields.is_some() && opts.bar_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--output", "--bar"]));
+        exit(1);
+    }
+    if opts.output_fields.is_some() && opts.wide_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--output", "--wide"]));
+        exit(1);
+    }
+    if opts.tree_flag && opts.reverse_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--tree", "--reverse"]));
+        exit(1);
+    }
+    if opts.inode_flag && opts.wide_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["-i", "--wide"]));
+        exit(1);
+    }
+    if opts.target_only_flag && opt_parser.operands().is_empty() && !opts.stdin_flag && opts.files_from.is_none() {
+        eprintln!("{}", gettext_fmt("option {} requires a file", &["--target-only"]));
+        exit(1);
+    }
+    if (opts.warning_percent.is_some() || opts.critical_percent.is_some()) && opts.output_format != OutputFormat::Check && opts.log_target.is_none() && !opts.problems_only_flag {
+        eprintln!("{}", gettext_fmt("options {} and {} require {}, {} or {}", &["-w", "-c", "--check", "--log", "--problems-only"]));
+        exit(1);
+    }
+    if opts.problems_only_flag && opts.output_format == OutputFormat::Check {
+        eprintln!("{}", gettext_fmt("options {} and {} are mutually exclusive", &["--problems-only", "--check"]));
+        exit(1);
+    }
+    if opts.problems_only_flag && opts.warning_percent.is_none() && opts.critical_percent.is_none() {
+        opts.warning_percent = Some(DEFAULT_PROBLEM_PERCENT);
+    }
+    if opts.log_target.is_some() && opts.warning_percent.is_none() && opts.critical_percent.is_none() {
+        eprintln!("{}", gettext_fmt("option {} requires {} or {}", &["--log", "-w", "-c"]));
+        exit(1);
+    }
+    if opts.alert_percent.is_some() && opts.alert_command.is_none() {
+        eprintln!("{}", gettext_fmt("option {} requires {}", &["--on-over", "--exec-alert"]));
+        exit(1);
+    }
+    if opts.alert_command.is_some() && opts.alert_percent.is_none() {
+        eprintln!("{}", gettext_fmt("option {} requires {}", &["--exec-alert", "--on-over"]));
+        exit(1);
+    }
+    if opts.target_only_flag && opts.output_format != OutputFormat::Table {
+        eprintln!("{}", gettext_fmt("option {} can be used only with the table output format", &["--target-only"]));
+        exit(1);
+    }
+    let mut status = 0;
+    let mut paths: Vec<String> = Vec::new();
+    let mut is_stdin_read = false;
+    for operand in opt_parser.operands() {
+        if operand == "-" {
+            if !is_stdin_read {
+                status |= read_paths(stdin().lock(), b'\n', &mut paths);
+                is_stdin_read = true;
+            }
+        } else {
+            paths.push(operand.clone());
+        }
+    }
+    if opts.stdin_flag && !is_stdin_read {
+        status |= read_paths(stdin().lock(), b'\n', &mut paths);
+    }
+    if let Some((file_name, delimiter)) = &opts.files_from {
+        if file_name == "-" {
+            status |= read_paths(stdin().lock(), *delimiter, &mut paths);
+        } else {
+            match fs::File::open(file_name) {
+                Ok(file) => status |= read_paths(BufReader::new(file), *delimiter, &mut paths),
+                Err(err) => {
+                    eprintln!("{}: {}", file_name, err);
+                    status = 1;
+                },
+            }
+        }
+    }
+    // The namespace is entered after reading the paths so that files with paths are read in the
+    // current namespace.
+    if let Some(namespace) = &opts.namespace {
+        if let Err(err) = setns_mount(namespace) {
+            eprintln!("{}: {}", namespace.to_string_lossy(), err);
+            exit(1);
+        }
+    }
+    let fields = selected_fields(&opts);
+    if fields.contains(&Field::Uuid) || fields.contains(&Field::Label) {
+        opts.disk_ids = Some(DiskIds::read(rooted_path("/dev/disk", &opts)));
+    }
+    let paths = if !opt_parser.operands().is_empty() || opts.stdin_flag || opts.files_from.is_some() { Some(paths.as_slice()) } else { None };
+    if let Some(watch_interval) = opts.watch_interval {
+        watch(paths, fields.as_slice(), watch_interval, &opts);
+    }
+    // The terminal size is got before the standard output is redirected for the pager.
+    let terminal_size = terminal_size();
+    let terminal_width = terminal_size.map(|(cols, _)| cols);
+    let pager = if opts.pager_flag && terminal_size.is_some() { Pager::start() } else { None };
+    status = print_usage(paths, fields.as_slice(), terminal_width, status, &mut PrintState::new(), &opts);
+    if let (Some(pager), Some((_, rows))) = (pager, terminal_size) {
+        pager.finish(rows);
     }
     exit(status);
 }
@@ -0,0 +1,47 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+/// Escapes pipes and backslashes in a cell of a Markdown table. Line breaks are replaced by spaces
+/// because a row must be in one line.
+pub fn markdown_cell(s: &str) -> String
+{
+    let mut t = String::new();
+    for c in s.chars() {
+        match c {
+            '|'         => t.push_str("\\|"),
+            '\\'        => t.push_str("\\\\"),
+            '\n' | '\r' => t.push(' '),
+            c           => t.push(c),
+        }
+    }
+    t
+}
+
+/// Formats cells as a row of a GitHub-flavored Markdown table.
+pub fn markdown_row<S: AsRef<str>>(cells: &[S]) -> String
+{
+    let escaped_cells: Vec<String> = cells.iter().map(|cell| markdown_cell(cell.as_ref())).collect();
+    format!("| {} |", escaped_cells.join(" | "))
+}
+
+/// Formats the delimiter row of a GitHub-flavored Markdown table. The columns are aligned to the
+/// left or to the right.
+pub fn markdown_delimiter_row(left_alignments: &[bool]) -> String
+{
+    let delimiters: Vec<&str> = left_alignments.iter().map(|is_left_aligned| if *is_left_aligned { ":---" } else { "---:" }).collect();
+    format!("| {} |", delimiters.join(" | "))
+}
@@ -0,0 +1,228 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::io::*;
+use std::path::*;
+
+/// An entry of the `/proc/self/mountinfo` file.
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct MountInfoEntry
+{
+    pub mount_id: u64,
+    pub parent_id: u64,
+    pub major: u32,
+    pub minor: u32,
+    pub root: PathBuf,
+    pub mount_point: PathBuf,
+    pub mount_options: String,
+    pub optional_fields: Vec<String>,
+    pub fs_type: String,
+    pub source: String,
+    pub super_options: String,
+}
+
+impl MountInfoEntry
+{
+    /// Returns `true` if the entry is a bind mount, that is when the mounted root isn't the root
+    /// of the filesystem.
+    pub fn is_bind_mount(&self) -> bool
+    {
+        self.root != Path::new("/")
+    }
+
+    /// Returns the propagation type of the mount, for example `shared` or `private`. A mount that is
+    /// shared and a slave at the same time has the `shared,slave` type.
+    pub fn propagation(&self) -> String
+    {
+        let mut types: Vec<&str> = Vec::new();
+        if self.optional_fields.iter().any(|field| field.starts_with("shared:")) {
+            types.push("shared");
+        }
+        if self.optional_fields.iter().any(|field| field.starts_with("master:")) {
+            types.push("slave");
+        }
+        if self.optional_fields.iter().any(|field| field == "unbindable") {
+            types.push("unbindable");
+        }
+        if types.is_empty() {
+            types.push("private");
+        }
+        types.join(",")
+    }
+}
+
+/// Unescapes octal escape sequences such as `\040` which are used by the kernel in mount paths.
+/// A backslash is kept if the escape sequence doesn't denote a byte.
+pub fn unescape_mount_path(s: &str) -> String
+{
+    let bytes = s.as_bytes();
+    let mut unescaped_bytes: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let code = if bytes[i] == b'\\' && i + 3 < bytes.len() && bytes[(i + 1)..(i + 4)].iter().all(|b| (b'0'..=b'7').contains(b)) {
+            Some(bytes[(i + 1)..(i + 4)].iter().fold(0u32, |code, b| code * 8 + u32::from(b - b'0')))
+        } else {
+            None
+        };
+        if let Some(code) = code.filter(|code| *code <= 0o377) {
+            unescaped_bytes.push(code as u8);
+            i += 4;
+        } else {
+            unescaped_bytes.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(unescaped_bytes.as_slice()).into_owned()
+}
+
+fn invalid_line_error(line_no: usize) -> Error
+{
+    Error::new(ErrorKind::InvalidData, format!("Invalid mountinfo line {}", line_no + 1))
+}
+
+fn parse_mountinfo_line(line: &str, line_no: usize) -> Result<MountInfoEntry>
+{
+    let mut tokens = line.split(' ');
+    let mut next_token = || tokens.next().ok_or_else(|| invalid_line_error(line_no));
+    let mount_id = next_token()?.parse::<u64>().map_err(|_| invalid_line_error(line_no))?;
+    let parent_id = next_token()?.parse::<u64>().map_err(|_| invalid_line_error(line_no))?;
+    let (major, minor) = match next_token()?.split_once(':') {
+        Some((major, minor)) => {
+            (major.parse::<u32>().map_err(|_| invalid_line_error(line_no))?,
+                minor.parse::<u32>().map_err(|_| invalid_line_error(line_no))?)
+        },
+        None => return Err(invalid_line_error(line_no)),
+    };
+    let root = PathBuf::from(unescape_mount_path(next_token()?));
+    let mount_point = PathBuf::from(unescape_mount_path(next_token()?));
+    let mount_options = String::from(next_token()?);
+    let mut optional_fields: Vec<String> = Vec::new();
+    loop {
+        let token = next_token()?;
+        if token == "-" {
+            break;
+        }
+        optional_fields.push(String::from(token));
+    }
+    let fs_type = String::from(next_token()?);
+    let source = unescape_mount_path(next_token()?);
+    let super_options = String::from(next_token()?);
+    Ok(MountInfoEntry {
+            mount_id,
+            parent_id,
+            major,
+            minor,
+            root,
+            mount_point,
+            mount_options,
+            optional_fields,
+            fs_type,
+            source,
+            super_options,
+    })
+}
+
+/// Reads the entries of a mountinfo file.
+pub fn read_mountinfo<P: AsRef<Path>>(path: P) -> Result<Vec<MountInfoEntry>>
+{
+    let file = fs::File::open(path)?;
+    let mut entries: Vec<MountInfoEntry> = Vec::new();
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if !line.is_empty() {
+            entries.push(parse_mountinfo_line(line.as_str(), line_no)?);
+        }
+    }
+    Ok(entries)
+}
+
+/// Finds the mountinfo entry for a mount point. If the mount point is overmounted, the last entry
+/// is returned because the last entry is visible.
+pub fn find_mountinfo_entry<P: AsRef<Path>>(entries: &[MountInfoEntry], mount_point: P) -> Option<&MountInfoEntry>
+{
+    entries.iter().rev().find(|entry| entry.mount_point == mount_point.as_ref())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_unescape_mount_path()
+    {
+        assert_eq!("/mnt/a b", unescape_mount_path("/mnt/a\\040b"));
+        assert_eq!("/mnt/a\tb\\c\n", unescape_mount_path("/mnt/a\\011b\\134c\\012"));
+        assert_eq!("/mnt/\u{e9}", unescape_mount_path("/mnt/\\303\\251"));
+        assert_eq!("/a\\400b", unescape_mount_path("/a\\400b"));
+        assert_eq!("/a\\777", unescape_mount_path("/a\\777"));
+        assert_eq!("/a\\04", unescape_mount_path("/a\\04"));
+        assert_eq!("/a\\08x", unescape_mount_path("/a\\08x"));
+        assert_eq!("/a\\", unescape_mount_path("/a\\"));
+    }
+
+    #[test]
+    fn test_parse_mountinfo_line_parses_optional_fields()
+    {
+        let entry = parse_mountinfo_line("36 35 98:0 /mnt1 /mnt/parent\\040dir rw,noatime master:1 shared:7 - ext3 /dev/root rw,errors=continue", 0).unwrap();
+        assert_eq!(36, entry.mount_id);
+        assert_eq!(35, entry.parent_id);
+        assert_eq!((98, 0), (entry.major, entry.minor));
+        assert_eq!(Path::new("/mnt1"), entry.root.as_path());
+        assert_eq!(Path::new("/mnt/parent dir"), entry.mount_point.as_path());
+        assert_eq!("rw,noatime", entry.mount_options);
+        assert_eq!(vec![String::from("master:1"), String::from("shared:7")], entry.optional_fields);
+        assert_eq!("ext3", entry.fs_type);
+        assert_eq!("/dev/root", entry.source);
+        assert_eq!("rw,errors=continue", entry.super_options);
+        assert!(entry.is_bind_mount());
+        assert_eq!("shared,slave", entry.propagation());
+    }
+
+    #[test]
+    fn test_parse_mountinfo_line_parses_line_without_optional_fields()
+    {
+        let entry = parse_mountinfo_line("22 1 0:21 / /proc rw,nosuid - proc proc rw", 0).unwrap();
+        assert!(entry.optional_fields.is_empty());
+        assert_eq!("proc", entry.fs_type);
+        assert!(!entry.is_bind_mount());
+        assert_eq!("private", entry.propagation());
+    }
+
+    #[test]
+    fn test_propagation()
+    {
+        let propagation = |fields: &str| parse_mountinfo_line(format!("1 0 0:1 / / rw {} - ext4 /dev/sda1 rw", fields).as_str(), 0).unwrap().propagation();
+        assert_eq!("shared", propagation("shared:1"));
+        assert_eq!("slave", propagation("master:2"));
+        assert_eq!("shared,slave", propagation("shared:3 master:4"));
+        assert_eq!("unbindable", propagation("unbindable"));
+    }
+
+    #[test]
+    fn test_parse_mountinfo_line_rejects_malformed_lines()
+    {
+        let error_message = |line: &str| parse_mountinfo_line(line, 2).unwrap_err().to_string();
+        assert_eq!("Invalid mountinfo line 3", error_message("x 1 0:21 / /proc rw - proc proc rw"));
+        assert_eq!("Invalid mountinfo line 3", error_message("22 1 021 / /proc rw - proc proc rw"));
+        assert_eq!("Invalid mountinfo line 3", error_message("22 1 0:x / /proc rw - proc proc rw"));
+        assert_eq!("Invalid mountinfo line 3", error_message("22 1 0:21 / /proc rw shared:1"));
+        assert_eq!("Invalid mountinfo line 3", error_message("22 1 0:21 / /proc rw - proc proc"));
+        assert_eq!("Invalid mountinfo line 3", error_message(""));
+    }
+}
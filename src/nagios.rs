@@ -0,0 +1,59 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+/// A state of a monitoring plugin for Nagios or Icinga.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PluginState
+{
+    Ok,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+impl PluginState
+{
+    /// Returns the name of the state in a plugin summary.
+    pub fn name(self) -> &'static str
+    {
+        match self {
+            PluginState::Ok       => "OK",
+            PluginState::Warning  => "WARNING",
+            PluginState::Critical => "CRITICAL",
+            PluginState::Unknown  => "UNKNOWN",
+        }
+    }
+
+    /// Returns the exit status of a plugin in the state.
+    pub fn exit_status(self) -> i32
+    {
+        match self {
+            PluginState::Ok       => 0,
+            PluginState::Warning  => 1,
+            PluginState::Critical => 2,
+            PluginState::Unknown  => 3,
+        }
+    }
+}
+
+/// Formats performance data of a plugin, for example `'/home'=1024B;800;900;0;1000`. The label is
+/// always quoted, so it can contain spaces and equal signs.
+pub fn perfdata(label: &str, value: u64, unit: &str, warning: Option<u64>, critical: Option<u64>, max: u64) -> String
+{
+    let warning = warning.map(|warning| warning.to_string()).unwrap_or_default();
+    let critical = critical.map(|critical| critical.to_string()).unwrap_or_default();
+    format!("'{}'={}{};{};{};0;{}", label.replace('\'', "''"), value, unit, warning, critical, max)
+}
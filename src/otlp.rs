@@ -0,0 +1,157 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use std::ffi::*;
+use std::io::*;
+use std::net::*;
+use std::time::Duration;
+use crate::json::*;
+
+/// A timeout of connecting to a collector, sending a request and receiving a response.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A value of a data point of a gauge.
+pub enum OtlpValue
+{
+    Int(u64),
+    /// A fraction in ten-thousandths.
+    Fraction(u64),
+}
+
+/// A data point of a gauge with attributes.
+pub struct OtlpDataPoint
+{
+    pub attributes: Vec<(&'static str, String)>,
+    pub value: OtlpValue,
+}
+
+/// A gauge metric with a name, a unit and data points.
+pub struct OtlpGauge
+{
+    pub name: &'static str,
+    pub unit: &'static str,
+    pub data_points: Vec<OtlpDataPoint>,
+}
+
+fn otlp_attributes(attributes: &[(&str, String)]) -> String
+{
+    let attributes: Vec<String> = attributes.iter().map(|(key, value)| {
+            format!("{{\"key\":{},\"value\":{{\"stringValue\":{}}}}}", json_string(key), json_string(value.as_str()))
+    }).collect();
+    format!("[{}]", attributes.join(","))
+}
+
+fn host_name() -> Option<String>
+{
+    let mut buf = [0 as libc::c_char; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr(), buf.len() - 1) } == 0 {
+        Some(unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy().into_owned())
+    } else {
+        None
+    }
+}
+
+/// Returns the resource attributes which are `service.name`, `host.name` and attributes from the
+/// `OTEL_RESOURCE_ATTRIBUTES` environment variable, for example `deployment.environment=prod`.
+fn resource_attributes() -> Vec<(String, String)>
+{
+    let mut attributes: Vec<(String, String)> = vec![(String::from("service.name"), String::from("mntdf"))];
+    if let Some(host_name) = host_name() {
+        attributes.push((String::from("host.name"), host_name));
+    }
+    if let Ok(value) = env::var("OTEL_RESOURCE_ATTRIBUTES") {
+        for (key, value) in value.split(',').filter_map(|pair| pair.split_once('=')) {
+            attributes.retain(|(other_key, _)| other_key != key.trim());
+            attributes.push((String::from(key.trim()), String::from(value.trim())));
+        }
+    }
+    attributes
+}
+
+/// Formats gauges as a request of the OTLP/HTTP protocol in the JSON encoding.
+pub fn otlp_metrics_request(gauges: &[OtlpGauge], time_unix_nano: u128) -> String
+{
+    let resource_attributes = resource_attributes();
+    let attributes: Vec<(&str, String)> = resource_attributes.iter().map(|(key, value)| (key.as_str(), value.clone())).collect();
+    let metrics: Vec<String> = gauges.iter().map(|gauge| {
+            let data_points: Vec<String> = gauge.data_points.iter().map(|data_point| {
+                    let value = match data_point.value {
+                        OtlpValue::Int(x)      => format!("\"asInt\":\"{}\"", x),
+                        OtlpValue::Fraction(x) => format!("\"asDouble\":{}.{:04}", x / 10000, x % 10000),
+                    };
+                    format!("{{\"attributes\":{},\"timeUnixNano\":\"{}\",{}}}", otlp_attributes(data_point.attributes.as_slice()), time_unix_nano, value)
+            }).collect();
+            format!("{{\"name\":{},\"unit\":{},\"gauge\":{{\"dataPoints\":[{}]}}}}", json_string(gauge.name), json_string(gauge.unit), data_points.join(","))
+    }).collect();
+    format!("{{\"resourceMetrics\":[{{\"resource\":{{\"attributes\":{}}},\"scopeMetrics\":[{{\"scope\":{{\"name\":\"mntdf\",\"version\":{}}},\"metrics\":[{}]}}]}}]}}",
+        otlp_attributes(attributes.as_slice()), json_string(env!("CARGO_PKG_VERSION")), metrics.join(","))
+}
+
+/// Splits an HTTP URL such as `http://localhost:4318` into an address with a port and a path.
+fn parse_http_url(url: &str) -> Result<(String, String)>
+{
+    let rest = match url.strip_prefix("http://") {
+        Some(rest) => rest,
+        None       => return Err(Error::new(ErrorKind::InvalidInput, "Only http URLs are supported")),
+    };
+    let (host_port, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None    => (rest, ""),
+    };
+    if host_port.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "URL without host"));
+    }
+    let has_port = match host_port.rfind(':') {
+        Some(i) => !host_port[i..].contains(']'),
+        None    => false,
+    };
+    let addr = if has_port { String::from(host_port) } else { format!("{}:80", host_port) };
+    Ok((addr, String::from(path)))
+}
+
+/// Sends a metrics request to the OTLP/HTTP endpoint of a collector, for example
+/// `http://localhost:4318`. The `/v1/metrics` path is appended to the endpoint unless the
+/// endpoint already ends with it.
+pub fn send_otlp(endpoint: &str, request: &str) -> Result<()>
+{
+    let (addr, path) = parse_http_url(endpoint)?;
+    let path = if path.ends_with("/v1/metrics") { path } else { format!("{}/v1/metrics", path.trim_end_matches('/')) };
+    let mut last_err = Error::new(ErrorKind::NotFound, "Can't resolve address");
+    let mut stream: Option<TcpStream> = None;
+    for socket_addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket_addr, TIMEOUT) {
+            Ok(tcp_stream) => {
+                stream = Some(tcp_stream);
+                break;
+            },
+            Err(err) => last_err = err,
+        }
+    }
+    let mut stream = stream.ok_or(last_err)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    write!(stream, "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", path, addr, request.len())?;
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    let status_line = status_line.trim_end();
+    match status_line.split(' ').nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(Error::other(format!("Unexpected response: {}", status_line))),
+    }
+}
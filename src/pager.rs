@@ -0,0 +1,109 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use std::fs;
+use std::io::*;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::io::FromRawFd;
+use std::os::unix::io::RawFd;
+use std::process::*;
+
+/// A pager which shows the standard output of this program.
+///
+/// The standard output is redirected to a temporary file until the output is complete, so that
+/// the pager is used only if the output is taller than the terminal.
+pub struct Pager
+{
+    command: String,
+    file: fs::File,
+    saved_stdout_fd: RawFd,
+}
+
+impl Pager
+{
+    /// Redirects the standard output to a temporary file for a pager from the `PAGER` environment
+    /// variable or `less`. `None` is returned if the pager is disabled by an empty `PAGER` or
+    /// `cat`, or if the standard output can't be redirected.
+    pub fn start() -> Option<Pager>
+    {
+        let command = env::var("PAGER").unwrap_or_else(|_| String::from("less"));
+        if command.is_empty() || command == "cat" {
+            return None;
+        }
+        let fp = unsafe { libc::tmpfile() };
+        if fp.is_null() {
+            return None;
+        }
+        let fd = unsafe { libc::dup(libc::fileno(fp)) };
+        unsafe { libc::fclose(fp) };
+        if fd == -1 {
+            return None;
+        }
+        let file = unsafe { fs::File::from_raw_fd(fd) };
+        stdout().flush().ok()?;
+        let saved_stdout_fd = unsafe { libc::dup(libc::STDOUT_FILENO) };
+        if saved_stdout_fd == -1 {
+            return None;
+        }
+        if unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } == -1 {
+            unsafe { libc::close(saved_stdout_fd) };
+            return None;
+        }
+        Some(Pager { command, file, saved_stdout_fd, })
+    }
+
+    /// Restores the standard output and shows the output in the pager if the output has at least
+    /// as many lines as the terminal has rows. Otherwise, or if the pager can't be run, the output
+    /// is written straight to the standard output.
+    pub fn finish(mut self, terminal_rows: usize)
+    {
+        let _ = stdout().flush();
+        unsafe { libc::dup2(self.saved_stdout_fd, libc::STDOUT_FILENO) };
+        unsafe { libc::close(self.saved_stdout_fd) };
+        let mut output: Vec<u8> = Vec::new();
+        if self.file.seek(SeekFrom::Start(0)).and_then(|_| self.file.read_to_end(&mut output)).is_err() {
+            return;
+        }
+        let line_count = output.iter().filter(|b| **b == b'\n').count();
+        // The number of rows is zero if it is unknown.
+        if terminal_rows == 0 || line_count < terminal_rows || !run_pager(self.command.as_str(), output.as_slice()) {
+            let _ = stdout().write_all(output.as_slice());
+            let _ = stdout().flush();
+        }
+    }
+}
+
+/// Runs the pager with the output. `false` is returned if the pager can't be started or fails,
+/// for example if it isn't found. A pager which is quitted by the user before the end of the
+/// output succeeds.
+fn run_pager(command: &str, output: &[u8]) -> bool
+{
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(_)    => return false,
+    };
+    // The write fails if the pager quits before the end of the output, so its result is ignored.
+    if let Some(mut child_stdin) = child.stdin.take() {
+        let _ = child_stdin.write_all(output);
+    }
+    child.wait().map(|exit_status| exit_status.success()).unwrap_or(false)
+}
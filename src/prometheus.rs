@@ -0,0 +1,29 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+/// Escapes a label value in the Prometheus text exposition format.
+pub fn prometheus_label_value(s: &str) -> String
+{
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Formats a sample of a metric with labels, for example
+/// `node_filesystem_size_bytes{device="/dev/sda1",fstype="ext4",mountpoint="/"} 1024`.
+pub fn prometheus_sample(name: &str, labels: &[(&str, &str)], value: i128) -> String
+{
+    let labels: Vec<String> = labels.iter().map(|(label, label_value)| format!("{}=\"{}\"", label, prometheus_label_value(label_value))).collect();
+    format!("{}{{{}}} {}", name, labels.join(","), value)
+}
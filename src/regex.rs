@@ -0,0 +1,98 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::ffi::*;
+use std::mem::MaybeUninit;
+use std::ptr::null_mut;
+use std::result;
+
+/// A POSIX extended regular expression.
+pub struct Regex
+{
+    regex: Box<libc::regex_t>,
+}
+
+impl Regex
+{
+    /// Compiles a regular expression.
+    pub fn new(pattern: &str) -> result::Result<Regex, String>
+    {
+        let pattern_cstring = match CString::new(pattern) {
+            Ok(pattern_cstring) => pattern_cstring,
+            Err(_)              => return Err(String::from("Invalid regular expression")),
+        };
+        let mut regex: Box<MaybeUninit<libc::regex_t>> = Box::new(MaybeUninit::uninit());
+        let res = unsafe { libc::regcomp(regex.as_mut_ptr(), pattern_cstring.as_ptr(), libc::REG_EXTENDED | libc::REG_NOSUB) };
+        if res == 0 {
+            Ok(Regex { regex: unsafe { Box::from_raw(Box::into_raw(regex) as *mut libc::regex_t) }, })
+        } else {
+            let mut buf = [0 as libc::c_char; 256];
+            unsafe { libc::regerror(res, regex.as_ptr(), buf.as_mut_ptr(), buf.len()) };
+            Err(unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy().into_owned())
+        }
+    }
+
+    /// Returns `true` if the regular expression matches a part of the string, otherwise `false`.
+    pub fn is_match(&self, s: &str) -> bool
+    {
+        match CString::new(s) {
+            Ok(s_cstring) => unsafe { libc::regexec(&*self.regex, s_cstring.as_ptr(), 0, null_mut(), 0) == 0 },
+            Err(_)        => false,
+        }
+    }
+}
+
+impl Drop for Regex
+{
+    fn drop(&mut self)
+    {
+        unsafe { libc::regfree(&mut *self.regex) };
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_regex_matches_part_of_string()
+    {
+        let regex = Regex::new("sd[a-z]").unwrap();
+        assert!(regex.is_match("/dev/sda1"));
+        assert!(!regex.is_match("/dev/nvme0n1p1"));
+        assert!(!regex.is_match("/dev/sd\0a"));
+    }
+
+    #[test]
+    fn test_regex_matches_anchored_pattern()
+    {
+        let regex = Regex::new("^/dev/sd[a-z]+$").unwrap();
+        assert!(regex.is_match("/dev/sda"));
+        assert!(regex.is_match("/dev/sdab"));
+        assert!(!regex.is_match("/dev/sda1"));
+        assert!(!regex.is_match("x/dev/sda"));
+        assert!(!regex.is_match("/dev/sd"));
+    }
+
+    #[test]
+    fn test_regex_rejects_invalid_pattern()
+    {
+        assert!(Regex::new("(sda").err().map(|err| !err.is_empty()).unwrap_or(false));
+        assert!(Regex::new("[a-").is_err());
+        assert_eq!(Some(String::from("Invalid regular expression")), Regex::new("a\0b").err());
+    }
+}
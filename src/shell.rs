@@ -0,0 +1,67 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use crate::value::*;
+
+/// Quotes a string for a POSIX shell with single quotes.
+pub fn shell_quote(s: &str) -> String
+{
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Formats a value as a shell word. Integers aren't quoted and a null value is an empty string.
+pub fn shell_value(value: &Value) -> String
+{
+    match value {
+        Value::Null       => String::from("''"),
+        Value::Integer(x) => format!("{}", x),
+        Value::String(s)  => shell_quote(s.as_str()),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_shell_quote()
+    {
+        assert_eq!("''", shell_quote(""));
+        assert_eq!("'/mnt/a b'", shell_quote("/mnt/a b"));
+        assert_eq!("'it'\\''s'", shell_quote("it's"));
+        assert_eq!("''\\'''\\'''", shell_quote("''"));
+        assert_eq!("'a\nb'", shell_quote("a\nb"));
+        assert_eq!("'$HOME `x` \\'", shell_quote("$HOME `x` \\"));
+    }
+
+    #[test]
+    fn test_shell_quote_is_read_back_by_shell()
+    {
+        let s = "it's a\n'quoted' $HOME `x` \\ \"path\"";
+        let output = Command::new("sh").arg("-c").arg(format!("printf %s {}", shell_quote(s))).output().unwrap();
+        assert_eq!(s.as_bytes(), output.stdout.as_slice());
+    }
+
+    #[test]
+    fn test_shell_value()
+    {
+        assert_eq!("''", shell_value(&Value::Null));
+        assert_eq!("-1", shell_value(&Value::Integer(-1)));
+        assert_eq!("'a'\\''b'", shell_value(&Value::String(String::from("a'b"))));
+    }
+}
@@ -0,0 +1,247 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+const BINARY_SUFFIXES: [&str; 9] = ["", "K", "M", "G", "T", "P", "E", "Z", "Y"];
+
+const SI_SUFFIXES: [&str; 9] = ["", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+/// The base of binary units (powers of 1024).
+pub const BINARY_BASE: u64 = 1024;
+
+/// The base of SI units (powers of 1000).
+pub const SI_BASE: u64 = 1000;
+
+/// A rounding mode of divisions.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Rounding
+{
+    Up,
+    Down,
+    Nearest,
+}
+
+impl Rounding
+{
+    /// Returns the rounding mode that has the specified name.
+    pub fn from_name(name: &str) -> Option<Rounding>
+    {
+        match name {
+            "up"      => Some(Rounding::Up),
+            "down"    => Some(Rounding::Down),
+            "nearest" => Some(Rounding::Nearest),
+            _         => None,
+        }
+    }
+
+    /// Divides `x` by `y` and rounds the quotient.
+    pub fn div(self, x: u128, y: u128) -> u128
+    {
+        match self {
+            Rounding::Up      => x.div_ceil(y),
+            Rounding::Down    => x / y,
+            Rounding::Nearest => (x + y / 2) / y,
+        }
+    }
+}
+
+/// Formats a size in bytes as a human-readable string, for example `1.5G`.
+///
+/// The base should be either `BINARY_BASE` or `SI_BASE`. Sizes are rounded according to the
+/// rounding mode. Values less than ten are printed with one decimal digit.
+pub fn format_human_size(size: u64, base: u64, rounding: Rounding) -> String
+{
+    let suffixes = if base == SI_BASE { &SI_SUFFIXES } else { &BINARY_SUFFIXES };
+    let base = base as u128;
+    let size = size as u128;
+    if size < base {
+        return format!("{}", size);
+    }
+    let mut exp = 0;
+    let mut unit: u128 = 1;
+    while size >= unit * base && exp + 1 < suffixes.len() {
+        unit *= base;
+        exp += 1;
+    }
+    let tenths = rounding.div(size * 10, unit);
+    if tenths < 100 {
+        return format!("{}.{}{}", tenths / 10, tenths % 10, suffixes[exp]);
+    }
+    let whole = rounding.div(size, unit);
+    if whole >= base && exp + 1 < suffixes.len() {
+        format!("1.0{}", suffixes[exp + 1])
+    } else {
+        format!("{}{}", whole, suffixes[exp])
+    }
+}
+
+/// Formats a size in bytes as a human-readable string with the specified number of decimal
+/// digits, for example `1.46G` for two digits.
+///
+/// Sizes are rounded as in `format_human_size`. Values less than the base are printed without
+/// decimal digits because they are numbers of bytes.
+pub fn format_human_size_with_precision(size: u64, base: u64, precision: usize, rounding: Rounding) -> String
+{
+    let suffixes = if base == SI_BASE { &SI_SUFFIXES } else { &BINARY_SUFFIXES };
+    let base = base as u128;
+    let size = size as u128;
+    if size < base {
+        return format!("{}", size);
+    }
+    let mut exp = 0;
+    let mut unit: u128 = 1;
+    while size >= unit * base && exp + 1 < suffixes.len() {
+        unit *= base;
+        exp += 1;
+    }
+    let scale = 10u128.pow(precision as u32);
+    let mut scaled = rounding.div(size * scale, unit);
+    // Rounding can carry the value to the next unit.
+    if scaled >= base * scale && exp + 1 < suffixes.len() {
+        unit *= base;
+        exp += 1;
+        scaled = rounding.div(size * scale, unit);
+    }
+    if precision > 0 {
+        format!("{}.{:0width$}{}", scaled / scale, scaled % scale, suffixes[exp], width = precision)
+    } else {
+        format!("{}{}", scaled, suffixes[exp])
+    }
+}
+
+const UNIT_LETTERS: &str = "KMGTPEZY";
+
+/// Parses a size with an optional suffix, for example `1M`, `4KiB` or `1GB`.
+///
+/// The suffixes without `B` and the suffixes with `iB` denote powers of 1024. The suffixes with
+/// `B` denote powers of 1000.
+pub fn parse_size(s: &str) -> Option<u64>
+{
+    let digit_count = s.chars().take_while(|c| c.is_ascii_digit()).count();
+    let (digits, suffix) = s.split_at(digit_count);
+    let number = if digits.is_empty() {
+        if suffix.is_empty() {
+            return None;
+        }
+        1
+    } else {
+        digits.parse::<u64>().ok()?
+    };
+    let mut suffix_chars = suffix.chars();
+    let multiplier = match suffix_chars.next() {
+        Some(c) => {
+            let exp = UNIT_LETTERS.find(c.to_ascii_uppercase())? as u32 + 1;
+            let base = match suffix_chars.as_str() {
+                "" | "iB" => BINARY_BASE,
+                "B"       => SI_BASE,
+                _         => return None,
+            };
+            base.checked_pow(exp)?
+        },
+        None => 1,
+    };
+    number.checked_mul(multiplier)
+}
+
+/// Formats a block size for a column header, for example `1M` for 1048576 or `1kB` for 1000.
+pub fn format_block_size(block_size: u64) -> String
+{
+    for (base, suffix) in [(BINARY_BASE, ""), (SI_BASE, "B")] {
+        let mut number = block_size;
+        let mut exp = 0;
+        while number.is_multiple_of(base) && exp < UNIT_LETTERS.len() {
+            number /= base;
+            exp += 1;
+        }
+        if exp > 0 {
+            let letter = if base == SI_BASE { SI_SUFFIXES[exp] } else { BINARY_SUFFIXES[exp] };
+            return format!("{}{}{}", number, letter, suffix);
+        }
+    }
+    format!("{}", block_size)
+}
+
+/// Formats a number with groups of three digits separated by the separator, for example
+/// `1,048,576`.
+pub fn format_grouped_number(number: u64, separator: &str) -> String
+{
+    let digits = format!("{}", number);
+    let mut s = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            s.push_str(separator);
+        }
+        s.push(c);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_parse_size_parses_numbers_without_suffixes()
+    {
+        assert_eq!(Some(0), parse_size("0"));
+        assert_eq!(Some(512), parse_size("512"));
+        assert_eq!(Some(u64::MAX), parse_size("18446744073709551615"));
+    }
+
+    #[test]
+    fn test_parse_size_parses_suffixes()
+    {
+        assert_eq!(Some(1024), parse_size("1K"));
+        assert_eq!(Some(1024), parse_size("1k"));
+        assert_eq!(Some(4096), parse_size("4KiB"));
+        assert_eq!(Some(1000), parse_size("1kB"));
+        assert_eq!(Some(3 * 1024 * 1024), parse_size("3M"));
+        assert_eq!(Some(1_000_000_000), parse_size("1GB"));
+        assert_eq!(Some(1 << 60), parse_size("1E"));
+        assert_eq!(Some(1_000_000_000_000_000_000), parse_size("1EB"));
+    }
+
+    #[test]
+    fn test_parse_size_parses_suffixes_without_numbers()
+    {
+        assert_eq!(Some(1024 * 1024), parse_size("M"));
+        assert_eq!(Some(1000), parse_size("KB"));
+    }
+
+    #[test]
+    fn test_parse_size_rejects_invalid_sizes()
+    {
+        assert_eq!(None, parse_size(""));
+        assert_eq!(None, parse_size("-1"));
+        assert_eq!(None, parse_size(" 1"));
+        assert_eq!(None, parse_size("1.5M"));
+        assert_eq!(None, parse_size("1X"));
+        assert_eq!(None, parse_size("1Ki"));
+        assert_eq!(None, parse_size("1Kb"));
+        assert_eq!(None, parse_size("1KiBB"));
+    }
+
+    #[test]
+    fn test_parse_size_rejects_overflows()
+    {
+        assert_eq!(None, parse_size("18446744073709551616"));
+        assert_eq!(None, parse_size("16E"));
+        assert_eq!(None, parse_size("17179869184G"));
+        assert_eq!(None, parse_size("1Z"));
+        assert_eq!(None, parse_size("1ZB"));
+        assert_eq!(None, parse_size("Y"));
+    }
+}
@@ -0,0 +1,288 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::fs;
+use std::path::*;
+use std::result;
+use std::time::*;
+use unicode_width::UnicodeWidthStr;
+use crate::args::{Opt, OptionDesc, Parser, print_option_help};
+use crate::args::OptName::*;
+use crate::growth::UsageSamples;
+use crate::i18n::gettext;
+use crate::json::*;
+use crate::size::*;
+
+const SNAPSHOT_OPTIONS: &[OptionDesc] = &[
+    OptionDesc { short_name: Some('a'), long_name: Some("all"), arg_name: None, is_arg_optional: false, description: "include filesystems that have zero blocks", },
+    OptionDesc { short_name: Some('l'), long_name: Some("local"), arg_name: None, is_arg_optional: false, description: "include only local filesystems", },
+    OptionDesc { short_name: Some('o'), long_name: Some("output"), arg_name: Some("FILE"), is_arg_optional: false, description: "write the snapshot to FILE instead of standard output", },
+    OptionDesc { short_name: None, long_name: Some("help"), arg_name: None, is_arg_optional: false, description: "display this help and exit", },
+];
+
+const DIFF_OPTIONS: &[OptionDesc] = &[
+    OptionDesc { short_name: Some('a'), long_name: Some("all"), arg_name: None, is_arg_optional: false, description: "include filesystems that have zero blocks if current usage is compared", },
+    OptionDesc { short_name: Some('H'), long_name: Some("si"), arg_name: None, is_arg_optional: false, description: "print differences in powers of 1000 (e.g., 1.1G)", },
+    OptionDesc { short_name: Some('h'), long_name: Some("human-readable"), arg_name: None, is_arg_optional: false, description: "print differences in powers of 1024 (e.g., 1023M)", },
+    OptionDesc { short_name: Some('l'), long_name: Some("local"), arg_name: None, is_arg_optional: false, description: "include only local filesystems if current usage is compared", },
+    OptionDesc { short_name: None, long_name: Some("help"), arg_name: None, is_arg_optional: false, description: "display this help and exit", },
+];
+
+/// Usage of a filesystem from a snapshot in bytes.
+struct SnapshotEntry
+{
+    mount_point: String,
+    total_bytes: i128,
+    used_bytes: i128,
+    available_bytes: i128,
+}
+
+/// Returns a snapshot of current usage as a JSON object with the time in seconds since the Unix
+/// epoch and an array of filesystems in the same format as the `--json` output.
+fn snapshot_json(opts: &crate::Options) -> String
+{
+    let fields = crate::selected_fields(opts);
+    let (usage_entries, _) = crate::get_usage_entries(None, fields.as_slice(), false, &mut UsageSamples::new(), opts);
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+    let objects: Vec<String> = usage_entries.iter().map(|usage_entry| format!("    {}", crate::usage_entry_to_json(usage_entry, fields.as_slice(), opts))).collect();
+    format!("{{\n  \"time\": {},\n  \"filesystems\": [\n{}\n  ]\n}}\n", time, objects.join(",\n"))
+}
+
+fn json_integer(value: Option<&JsonValue>) -> Option<i128>
+{
+    match value {
+        Some(JsonValue::Number(number)) => number.parse::<i128>().ok(),
+        _                               => None,
+    }
+}
+
+fn parse_snapshot(s: &str) -> result::Result<Vec<SnapshotEntry>, String>
+{
+    let snapshot = parse_json(s)?;
+    let objects = match snapshot.get("filesystems") {
+        Some(JsonValue::Array(objects)) => objects,
+        _                               => return Err(String::from(gettext("Invalid snapshot"))),
+    };
+    let mut entries: Vec<SnapshotEntry> = Vec::new();
+    for object in objects.iter() {
+        let mount_point = match object.get("target") {
+            Some(JsonValue::String(mount_point)) => mount_point.clone(),
+            _                                    => return Err(String::from(gettext("Invalid snapshot"))),
+        };
+        match (json_integer(object.get("size")), json_integer(object.get("used")), json_integer(object.get("avail"))) {
+            (Some(total_bytes), Some(used_bytes), Some(available_bytes)) => entries.push(SnapshotEntry { mount_point, total_bytes, used_bytes, available_bytes, }),
+            _ => return Err(String::from(gettext("Invalid snapshot"))),
+        }
+    }
+    Ok(entries)
+}
+
+fn read_snapshot(path: &Path) -> result::Result<Vec<SnapshotEntry>, String>
+{
+    let s = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.to_string_lossy(), err))?;
+    parse_snapshot(s.as_str()).map_err(|err| format!("{}: {}", path.to_string_lossy(), err))
+}
+
+/// Formats a size in the human-readable unit of the base or in 1K-blocks which are rounded up as
+/// df does.
+pub fn format_size(size: u64, base: Option<u64>) -> String
+{
+    match base {
+        Some(base) => format_human_size(size, base, Rounding::Up),
+        None       => format!("{}", Rounding::Up.div(u128::from(size), 1024)),
+    }
+}
+
+/// Formats a difference of sizes with a sign.
+fn format_difference(difference: i128, base: Option<u64>) -> String
+{
+    let sign = if difference > 0 { "+" } else if difference < 0 { "-" } else { "" };
+    format!("{}{}", sign, format_size(difference.unsigned_abs().min(u128::from(u64::MAX)) as u64, base))
+}
+
+/// Prints rows with aligned columns. The first column is aligned to the left and other columns
+/// are aligned to the right.
+pub fn print_rows(rows: &[Vec<String>])
+{
+    let column_count = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..column_count).map(|i| rows.iter().filter_map(|row| row.get(i)).map(|value| value.width()).max().unwrap_or(0)).collect();
+    for row in rows.iter() {
+        let values: Vec<String> = row.iter().enumerate().map(|(i, value)| crate::pad_value(value.as_str(), widths[i], i == 0)).collect();
+        println!("{}", values.join(" ").trim_end());
+    }
+}
+
+fn find_sizes(entries: &[SnapshotEntry], mount_point: &str) -> (i128, i128, i128)
+{
+    match entries.iter().find(|entry| entry.mount_point == mount_point) {
+        Some(entry) => (entry.total_bytes, entry.used_bytes, entry.available_bytes),
+        None        => (0, 0, 0),
+    }
+}
+
+/// Prints differences between filesystems of two snapshots. A filesystem which is only in one
+/// snapshot is compared with an empty filesystem.
+fn print_differences(old_entries: &[SnapshotEntry], new_entries: &[SnapshotEntry], base: Option<u64>)
+{
+    let mut mount_points: Vec<&str> = new_entries.iter().map(|entry| entry.mount_point.as_str()).collect();
+    for old_entry in old_entries.iter() {
+        if !mount_points.contains(&old_entry.mount_point.as_str()) {
+            mount_points.push(old_entry.mount_point.as_str());
+        }
+    }
+    let size_header = if base.is_some() { gettext("Size") } else { "1K-blocks" };
+    let mut rows: Vec<Vec<String>> = vec![vec![String::from(gettext("Mounted on")), String::from(size_header), String::from(gettext("Used")), String::from(gettext("Avail"))]];
+    for mount_point in mount_points.iter() {
+        let (old_total, old_used, old_available) = find_sizes(old_entries, mount_point);
+        let (new_total, new_used, new_available) = find_sizes(new_entries, mount_point);
+        rows.push(vec![
+                String::from(*mount_point),
+                format_difference(new_total.saturating_sub(old_total), base),
+                format_difference(new_used.saturating_sub(old_used), base),
+                format_difference(new_available.saturating_sub(old_available), base),
+        ]);
+    }
+    print_rows(rows.as_slice());
+}
+
+fn print_snapshot_help(program_name: &str)
+{
+    println!("Usage: {} snapshot [OPTION]...", program_name);
+    println!("Save usage of filesystems as a JSON snapshot which can be compared by the diff");
+    println!("subcommand.");
+    println!();
+    println!("Options:");
+    print_option_help(SNAPSHOT_OPTIONS);
+}
+
+fn print_diff_help(program_name: &str)
+{
+    println!("Usage: {} diff [OPTION]... OLD [NEW]", program_name);
+    println!("Print differences in size, used space and available space of filesystems between");
+    println!("the OLD snapshot and the NEW snapshot, or current usage if NEW isn't specified.");
+    println!();
+    println!("Options:");
+    print_option_help(DIFF_OPTIONS);
+}
+
+/// Runs the `snapshot` subcommand and returns an exit status. The first argument should be the
+/// subcommand name.
+pub fn snapshot_main(program_name: &str, args: &[String]) -> i32
+{
+    let mut opt_parser = Parser::new(args, SNAPSHOT_OPTIONS);
+    let mut opts = crate::Options::new();
+    let mut output_path: Option<PathBuf> = None;
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt(Short('a'), _))) => opts.all_flag = true,
+            Some(Ok(Opt(Short('l'), _))) => opts.local_flag = true,
+            Some(Ok(Opt(Short('o'), Some(opt_arg)))) => output_path = Some(PathBuf::from(opt_arg)),
+            Some(Ok(Opt(Long("help"), _))) => {
+                print_snapshot_help(program_name);
+                return 0;
+            },
+            Some(Ok(Opt(name, _))) => {
+                eprintln!("{} -- {:?}", gettext("unknown option"), name);
+                return 1;
+            },
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    if !opt_parser.operands().is_empty() {
+        eprintln!("{}", gettext("too many arguments"));
+        return 1;
+    }
+    opts.output_format = crate::OutputFormat::Json;
+    let snapshot = snapshot_json(&opts);
+    match output_path {
+        Some(output_path) => {
+            if let Err(err) = fs::write(output_path.as_path(), snapshot) {
+                eprintln!("{}: {}", output_path.to_string_lossy(), err);
+                return 1;
+            }
+        },
+        None => print!("{}", snapshot),
+    }
+    0
+}
+
+/// Runs the `diff` subcommand and returns an exit status. The first argument should be the
+/// subcommand name.
+pub fn diff_main(program_name: &str, args: &[String]) -> i32
+{
+    let mut opt_parser = Parser::new(args, DIFF_OPTIONS);
+    let mut opts = crate::Options::new();
+    let mut base: Option<u64> = None;
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt(Short('a'), _))) => opts.all_flag = true,
+            Some(Ok(Opt(Short('h'), _))) => base = Some(BINARY_BASE),
+            Some(Ok(Opt(Short('H'), _))) => base = Some(SI_BASE),
+            Some(Ok(Opt(Short('l'), _))) => opts.local_flag = true,
+            Some(Ok(Opt(Long("help"), _))) => {
+                print_diff_help(program_name);
+                return 0;
+            },
+            Some(Ok(Opt(name, _))) => {
+                eprintln!("{} -- {:?}", gettext("unknown option"), name);
+                return 1;
+            },
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    let (old_path, new_path) = match opt_parser.operands() {
+        [old_path]           => (Path::new(old_path), None),
+        [old_path, new_path] => (Path::new(old_path), Some(Path::new(new_path))),
+        [] => {
+            eprintln!("{}", gettext("missing operand"));
+            return 1;
+        },
+        _ => {
+            eprintln!("{}", gettext("too many arguments"));
+            return 1;
+        },
+    };
+    opts.output_format = crate::OutputFormat::Json;
+    let old_entries = match read_snapshot(old_path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 1;
+        },
+    };
+    let new_entries = match new_path {
+        Some(new_path) => read_snapshot(new_path),
+        None           => parse_snapshot(snapshot_json(&opts).as_str()),
+    };
+    match new_entries {
+        Ok(new_entries) => {
+            print_differences(old_entries.as_slice(), new_entries.as_slice(), base);
+            0
+        },
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        },
+    }
+}
@@ -0,0 +1,173 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::ffi::*;
+use std::path::Path;
+use std::ptr::null;
+use std::ptr::null_mut;
+use std::result;
+
+#[allow(non_camel_case_types)]
+enum sqlite3 {}
+
+#[allow(non_camel_case_types)]
+enum sqlite3_stmt {}
+
+const SQLITE_OK: c_int = 0;
+const SQLITE_ROW: c_int = 100;
+const SQLITE_DONE: c_int = 101;
+const SQLITE_OPEN_READONLY: c_int = 0x1;
+const SQLITE_OPEN_READWRITE: c_int = 0x2;
+const SQLITE_OPEN_CREATE: c_int = 0x4;
+
+/// The special destructor which makes SQLite copy a bound string.
+const SQLITE_TRANSIENT: isize = -1;
+
+/// A timeout of waiting for a lock of a database which is used by another process.
+const BUSY_TIMEOUT_MS: c_int = 5000;
+
+#[link(name = "sqlite3")]
+extern "C" {
+    fn sqlite3_open_v2(filename: *const c_char, db: *mut *mut sqlite3, flags: c_int, vfs: *const c_char) -> c_int;
+    fn sqlite3_close(db: *mut sqlite3) -> c_int;
+    fn sqlite3_errmsg(db: *mut sqlite3) -> *const c_char;
+    fn sqlite3_busy_timeout(db: *mut sqlite3, ms: c_int) -> c_int;
+    fn sqlite3_prepare_v2(db: *mut sqlite3, sql: *const c_char, len: c_int, stmt: *mut *mut sqlite3_stmt, tail: *mut *const c_char) -> c_int;
+    fn sqlite3_bind_int64(stmt: *mut sqlite3_stmt, index: c_int, value: i64) -> c_int;
+    fn sqlite3_bind_text(stmt: *mut sqlite3_stmt, index: c_int, value: *const c_char, len: c_int, destructor: isize) -> c_int;
+    fn sqlite3_step(stmt: *mut sqlite3_stmt) -> c_int;
+    fn sqlite3_reset(stmt: *mut sqlite3_stmt) -> c_int;
+    fn sqlite3_column_int64(stmt: *mut sqlite3_stmt, index: c_int) -> i64;
+    fn sqlite3_finalize(stmt: *mut sqlite3_stmt) -> c_int;
+}
+
+/// A connection to an SQLite database.
+pub struct Database
+{
+    db: *mut sqlite3,
+}
+
+impl Database
+{
+    /// Opens a database. A database which is opened for writing is created if it doesn't exist.
+    pub fn open(path: &Path, is_writable: bool) -> result::Result<Database, String>
+    {
+        let path_cstring = CString::new(path.to_string_lossy().into_owned()).map_err(|err| err.to_string())?;
+        let flags = if is_writable { SQLITE_OPEN_READWRITE | SQLITE_OPEN_CREATE } else { SQLITE_OPEN_READONLY };
+        let mut db: *mut sqlite3 = null_mut();
+        let res = unsafe { sqlite3_open_v2(path_cstring.as_ptr(), &mut db, flags, null()) };
+        let database = Database { db, };
+        if res != SQLITE_OK {
+            return Err(database.error_message());
+        }
+        unsafe { sqlite3_busy_timeout(database.db, BUSY_TIMEOUT_MS) };
+        Ok(database)
+    }
+
+    fn error_message(&self) -> String
+    {
+        if self.db.is_null() {
+            return String::from("Out of memory");
+        }
+        unsafe { CStr::from_ptr(sqlite3_errmsg(self.db)) }.to_string_lossy().into_owned()
+    }
+
+    /// Compiles an SQL statement.
+    pub fn prepare(&self, sql: &str) -> result::Result<Statement<'_>, String>
+    {
+        let sql_cstring = CString::new(sql).map_err(|err| err.to_string())?;
+        let mut stmt: *mut sqlite3_stmt = null_mut();
+        if unsafe { sqlite3_prepare_v2(self.db, sql_cstring.as_ptr(), -1, &mut stmt, null_mut()) } != SQLITE_OK {
+            return Err(self.error_message());
+        }
+        Ok(Statement { database: self, stmt, })
+    }
+
+    /// Executes an SQL statement which doesn't return rows.
+    pub fn execute(&self, sql: &str) -> result::Result<(), String>
+    {
+        let mut stmt = self.prepare(sql)?;
+        while stmt.step()? {}
+        Ok(())
+    }
+}
+
+impl Drop for Database
+{
+    fn drop(&mut self)
+    {
+        unsafe { sqlite3_close(self.db) };
+    }
+}
+
+/// A compiled SQL statement. Parameters are indexed from 1 and columns are indexed from 0 as in
+/// the SQLite API.
+pub struct Statement<'a>
+{
+    database: &'a Database,
+    stmt: *mut sqlite3_stmt,
+}
+
+impl<'a> Statement<'a>
+{
+    fn check(&self, res: c_int) -> result::Result<(), String>
+    {
+        if res == SQLITE_OK { Ok(()) } else { Err(self.database.error_message()) }
+    }
+
+    pub fn bind_int64(&mut self, index: usize, value: i64) -> result::Result<(), String>
+    {
+        let res = unsafe { sqlite3_bind_int64(self.stmt, index as c_int, value) };
+        self.check(res)
+    }
+
+    pub fn bind_text(&mut self, index: usize, value: &str) -> result::Result<(), String>
+    {
+        let res = unsafe { sqlite3_bind_text(self.stmt, index as c_int, value.as_ptr() as *const c_char, value.len() as c_int, SQLITE_TRANSIENT) };
+        self.check(res)
+    }
+
+    /// Executes the next step of the statement and returns `true` if a row is available. The
+    /// statement is reset after the last step, so that it can be executed again.
+    pub fn step(&mut self) -> result::Result<bool, String>
+    {
+        match unsafe { sqlite3_step(self.stmt) } {
+            SQLITE_ROW  => Ok(true),
+            SQLITE_DONE => {
+                unsafe { sqlite3_reset(self.stmt) };
+                Ok(false)
+            },
+            _ => {
+                let message = self.database.error_message();
+                unsafe { sqlite3_reset(self.stmt) };
+                Err(message)
+            },
+        }
+    }
+
+    pub fn column_int64(&self, index: usize) -> i64
+    {
+        unsafe { sqlite3_column_int64(self.stmt, index as c_int) }
+    }
+}
+
+impl<'a> Drop for Statement<'a>
+{
+    fn drop(&mut self)
+    {
+        unsafe { sqlite3_finalize(self.stmt) };
+    }
+}
@@ -0,0 +1,62 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::io::*;
+use std::net::*;
+
+/// The maximal size of a packet that doesn't need fragmentation on common networks.
+const MAX_PACKET_LEN: usize = 1432;
+
+/// Converts a mount point to a part of a metric name, for example `/var/log` to `var_log`. The
+/// root directory is `root`.
+pub fn statsd_name(mount_point: &str) -> String
+{
+    let name: String = mount_point.trim_start_matches('/').chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect();
+    if name.is_empty() { String::from("root") } else { name }
+}
+
+/// Formats a gauge metric, for example `mntdf.root.used:1024|g`.
+pub fn statsd_gauge(name: &str, value: u64) -> String
+{
+    format!("{}:{}|g", name, value)
+}
+
+/// Sends metrics to a StatsD server over UDP. Metrics are joined by newlines into packets which
+/// don't exceed the maximal packet size.
+pub fn send_statsd(addr: &str, metrics: &[String]) -> Result<()>
+{
+    let server_addr = match addr.to_socket_addrs()?.next() {
+        Some(server_addr) => server_addr,
+        None              => return Err(Error::new(ErrorKind::NotFound, "Can't resolve address")),
+    };
+    let local_addr = if server_addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = UdpSocket::bind(local_addr)?;
+    let mut packet = String::new();
+    for metric in metrics.iter() {
+        if !packet.is_empty() && packet.len() + 1 + metric.len() > MAX_PACKET_LEN {
+            socket.send_to(packet.as_bytes(), server_addr)?;
+            packet.clear();
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(metric.as_str());
+    }
+    if !packet.is_empty() {
+        socket.send_to(packet.as_bytes(), server_addr)?;
+    }
+    Ok(())
+}
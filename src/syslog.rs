@@ -0,0 +1,98 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::io::*;
+use std::os::unix::net::UnixDatagram;
+
+/// The socket of the native protocol of the systemd journal.
+const JOURNAL_SOCKET_PATH: &str = "/run/systemd/journal/socket";
+
+/// A destination of log messages.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LogTarget
+{
+    Syslog,
+    Journal,
+}
+
+impl LogTarget
+{
+    /// Returns the log target that has the specified name.
+    pub fn from_name(name: &str) -> Option<LogTarget>
+    {
+        match name {
+            "syslog"  => Some(LogTarget::Syslog),
+            "journal" => Some(LogTarget::Journal),
+            _         => None,
+        }
+    }
+}
+
+/// Appends a field in the native journal protocol. A value with a newline is prefixed by its
+/// length as a 64-bit little-endian number.
+fn push_journal_field(data: &mut Vec<u8>, name: &str, value: &str)
+{
+    data.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        data.push(b'\n');
+        data.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        data.push(b'=');
+    }
+    data.extend_from_slice(value.as_bytes());
+    data.push(b'\n');
+}
+
+fn send_journal(priority: libc::c_int, message: &str, fields: &[(&str, String)]) -> Result<()>
+{
+    let mut data: Vec<u8> = Vec::new();
+    push_journal_field(&mut data, "MESSAGE", message);
+    push_journal_field(&mut data, "PRIORITY", format!("{}", priority).as_str());
+    push_journal_field(&mut data, "SYSLOG_IDENTIFIER", "mntdf");
+    for (name, value) in fields.iter() {
+        push_journal_field(&mut data, name, value.as_str());
+    }
+    let socket = UnixDatagram::unbound()?;
+    socket.send_to(data.as_slice(), JOURNAL_SOCKET_PATH)?;
+    Ok(())
+}
+
+fn send_syslog(priority: libc::c_int, message: &str, fields: &[(&str, String)])
+{
+    // Syslog doesn't have structured fields, so fields are appended to the message.
+    let mut line = String::from(message);
+    for (name, value) in fields.iter() {
+        line.push_str(format!(" {}={}", name, value).as_str());
+    }
+    let line_bytes: Vec<u8> = line.bytes().filter(|b| *b != 0).chain(Some(0)).collect();
+    unsafe {
+        libc::openlog(b"mntdf\0".as_ptr() as *const libc::c_char, libc::LOG_PID, libc::LOG_DAEMON);
+        libc::syslog(priority, b"%s\0".as_ptr() as *const libc::c_char, line_bytes.as_ptr() as *const libc::c_char);
+    }
+}
+
+/// Logs a message with a priority such as `libc::LOG_WARNING` and with fields such as
+/// `MNTDF_TARGET`.
+pub fn log_message(target: LogTarget, priority: libc::c_int, message: &str, fields: &[(&str, String)]) -> Result<()>
+{
+    match target {
+        LogTarget::Syslog => {
+            send_syslog(priority, message, fields);
+            Ok(())
+        },
+        LogTarget::Journal => send_journal(priority, message, fields),
+    }
+}
@@ -0,0 +1,189 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::result;
+use crate::field::*;
+use crate::i18n::gettext_fmt;
+use crate::size::*;
+use crate::unit::*;
+
+/// An item of an output template.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TemplateItem
+{
+    /// A literal text.
+    Text(String),
+    /// A placeholder of a field with an optional unit of sizes.
+    Placeholder(Field, Option<SizeUnit>),
+}
+
+fn parse_template_unit(s: &str) -> result::Result<SizeUnit, String>
+{
+    let size_unit = match s {
+        "h" => Some(SizeUnit::Human(BINARY_BASE)),
+        "H" => Some(SizeUnit::Human(SI_BASE)),
+        _   => parse_size_unit(s),
+    };
+    size_unit.ok_or_else(|| gettext_fmt("option {}: unit {} invalid", &["--format", format!("{:?}", s).as_str()]))
+}
+
+fn parse_placeholder(s: &str) -> result::Result<TemplateItem, String>
+{
+    let (name, unit) = match s.split_once(':') {
+        Some((name, unit)) => (name, Some(unit)),
+        None               => (s, None),
+    };
+    let field = Field::from_name(name).ok_or_else(|| gettext_fmt("option {}: field {} unknown", &["--format", format!("{:?}", name).as_str()]))?;
+    let size_unit = match unit {
+        Some(unit) => Some(parse_template_unit(unit)?),
+        None       => None,
+    };
+    Ok(TemplateItem::Placeholder(field, size_unit))
+}
+
+/// Parses an output template such as `{target} {avail:h} {pcent}`.
+///
+/// A placeholder is a field name with an optional unit after a colon: `h` for powers of 1024,
+/// `H` for powers of 1000 or a block size. `{{` and `}}` are literal braces, and `\n`, `\t` and
+/// `\\` are escape sequences.
+pub fn parse_template(s: &str) -> result::Result<Vec<TemplateItem>, String>
+{
+    let mut items: Vec<TemplateItem> = Vec::new();
+    let mut text = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            },
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c)   => placeholder.push(c),
+                        None      => return Err(gettext_fmt("option {}: unterminated placeholder", &["--format"])),
+                    }
+                }
+                if !text.is_empty() {
+                    items.push(TemplateItem::Text(text));
+                    text = String::new();
+                }
+                items.push(parse_placeholder(placeholder.as_str())?);
+            },
+            '}' => return Err(gettext_fmt("option {}: unmatched '}'", &["--format"])),
+            '\\' => {
+                match chars.next() {
+                    Some('n')  => text.push('\n'),
+                    Some('t')  => text.push('\t'),
+                    Some('\\') => text.push('\\'),
+                    Some(c) => {
+                        text.push('\\');
+                        text.push(c);
+                    },
+                    None       => text.push('\\'),
+                }
+            },
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        items.push(TemplateItem::Text(text));
+    }
+    Ok(items)
+}
+
+/// Returns the fields of the placeholders of the template without duplicates.
+pub fn template_fields(items: &[TemplateItem]) -> Vec<Field>
+{
+    let mut fields: Vec<Field> = Vec::new();
+    for item in items.iter() {
+        if let TemplateItem::Placeholder(field, _) = item {
+            if !fields.contains(field) {
+                fields.push(*field);
+            }
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn text(s: &str) -> TemplateItem
+    {
+        TemplateItem::Text(String::from(s))
+    }
+
+    #[test]
+    fn test_parse_template_parses_placeholders_and_text()
+    {
+        let items = vec![
+            TemplateItem::Placeholder(Field::Target, None),
+            text(": "),
+            TemplateItem::Placeholder(Field::Avail, Some(SizeUnit::Human(BINARY_BASE))),
+            text(" "),
+            TemplateItem::Placeholder(Field::Used, Some(SizeUnit::Human(SI_BASE))),
+            text(" "),
+            TemplateItem::Placeholder(Field::Size, Some(SizeUnit::Blocks(1024 * 1024))),
+        ];
+        assert_eq!(Ok(items), parse_template("{target}: {avail:h} {used:H} {size:1M}"));
+        assert_eq!(Ok(Vec::new()), parse_template(""));
+    }
+
+    #[test]
+    fn test_parse_template_parses_braces_and_escapes()
+    {
+        assert_eq!(Ok(vec![text("{x}\n\t\\")]), parse_template("{{x}}\\n\\t\\\\"));
+        assert_eq!(Ok(vec![text("\\q\\")]), parse_template("\\q\\"));
+        assert_eq!(Ok(vec![text("{"), TemplateItem::Placeholder(Field::Pcent, None), text("}")]), parse_template("{{{pcent}}}"));
+    }
+
+    #[test]
+    fn test_parse_template_rejects_unterminated_placeholders()
+    {
+        assert!(parse_template("{").is_err());
+        assert!(parse_template("{target").is_err());
+        assert!(parse_template("{target} {avail:h").is_err());
+        assert!(parse_template("{target}{{{").is_err());
+    }
+
+    #[test]
+    fn test_parse_template_rejects_invalid_placeholders()
+    {
+        assert!(parse_template("}").is_err());
+        assert!(parse_template("{target}}").is_err());
+        assert!(parse_template("{}").is_err());
+        assert!(parse_template("{foo}").is_err());
+        assert!(parse_template("{target{avail}").is_err());
+        assert!(parse_template("{avail:}").is_err());
+        assert!(parse_template("{avail:x}").is_err());
+    }
+
+    #[test]
+    fn test_template_fields_removes_duplicates()
+    {
+        let items = parse_template("{target} {avail} {target}").unwrap();
+        assert_eq!(vec![Field::Target, Field::Avail], template_fields(items.as_slice()));
+    }
+}
@@ -0,0 +1,36 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::mem::MaybeUninit;
+
+/// Returns the number of columns and the number of rows of the terminal of the standard output.
+/// `None` is returned if the standard output isn't a terminal or the terminal has no columns. The
+/// number of rows is zero if it is unknown.
+pub fn terminal_size() -> Option<(usize, usize)>
+{
+    let mut winsize: MaybeUninit<libc::winsize> = MaybeUninit::uninit();
+    let res = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, winsize.as_mut_ptr()) };
+    if res != -1 {
+        let winsize = unsafe { winsize.assume_init_ref() };
+        if winsize.ws_col != 0 {
+            Some((winsize.ws_col as usize, winsize.ws_row as usize))
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
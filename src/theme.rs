@@ -0,0 +1,144 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::collections::HashSet;
+use std::fs;
+use std::io::*;
+use std::path::*;
+use crate::color::*;
+use crate::field::*;
+use crate::toml::*;
+
+/// A theme of colored output.
+pub struct Theme
+{
+    /// Styles of capacities from thresholds in percents, in ascending order of the thresholds.
+    pub capacity_styles: Vec<(u64, String)>,
+    pub column_styles: Vec<(Field, String)>,
+    pub dim_style: String,
+    pub is_pseudo_dimmed: bool,
+    pub is_read_only_dimmed: bool,
+    pub dimmed_types: HashSet<String>,
+}
+
+fn invalid_theme_error(msg: String) -> Error
+{
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn style_value(table: &str, key: &str, value: &TomlValue) -> Result<String>
+{
+    match value {
+        TomlValue::String(s) => parse_style(s.as_str()).map_err(|err| invalid_theme_error(format!("{}.{}: {}", table, key, err))),
+        _                    => Err(invalid_theme_error(format!("{}.{}: expected string", table, key))),
+    }
+}
+
+fn bool_value(table: &str, key: &str, value: &TomlValue) -> Result<bool>
+{
+    match value {
+        TomlValue::Boolean(b) => Ok(*b),
+        _                     => Err(invalid_theme_error(format!("{}.{}: expected boolean", table, key))),
+    }
+}
+
+impl Theme
+{
+    /// Creates the default theme. Capacities are green below 70%, yellow below 90% and red from
+    /// 90%, and pseudo filesystems are dimmed.
+    pub fn new() -> Theme
+    {
+        Theme {
+            capacity_styles: vec![(0, String::from("32")), (70, String::from("33")), (90, String::from("31"))],
+            column_styles: Vec::new(),
+            dim_style: String::from("2"),
+            is_pseudo_dimmed: true,
+            is_read_only_dimmed: false,
+            dimmed_types: HashSet::new(),
+        }
+    }
+
+    /// Reads a theme from a TOML file.
+    ///
+    /// The `capacity` table maps thresholds to styles of capacities, the `columns` table maps field
+    /// names to styles of columns, and the `dim` table has the `style`, `pseudo`, `read-only` and
+    /// `types` keys. Settings which aren't in the file are default.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Theme>
+    {
+        let document = parse_toml(fs::read_to_string(path)?.as_str()).map_err(invalid_theme_error)?;
+        let mut theme = Theme::new();
+        for table in document.tables.iter() {
+            match table.name.as_str() {
+                "" if table.pairs.is_empty() => (),
+                "capacity" => {
+                    theme.capacity_styles.clear();
+                    for (key, value) in table.pairs.iter() {
+                        match key.parse::<u64>() {
+                            Ok(threshold) => theme.capacity_styles.push((threshold, style_value("capacity", key, value)?)),
+                            Err(_)        => return Err(invalid_theme_error(format!("capacity.{}: invalid threshold", key))),
+                        }
+                    }
+                    theme.capacity_styles.sort_by_key(|pair| pair.0);
+                },
+                "columns" => {
+                    for (key, value) in table.pairs.iter() {
+                        match Field::from_name(key) {
+                            Some(field) => theme.column_styles.push((field, style_value("columns", key, value)?)),
+                            None        => return Err(invalid_theme_error(format!("columns.{}: field unknown", key))),
+                        }
+                    }
+                },
+                "dim" => {
+                    for (key, value) in table.pairs.iter() {
+                        match (key.as_str(), value) {
+                            ("style", _)     => theme.dim_style = style_value("dim", key, value)?,
+                            ("pseudo", _)    => theme.is_pseudo_dimmed = bool_value("dim", key, value)?,
+                            ("read-only", _) => theme.is_read_only_dimmed = bool_value("dim", key, value)?,
+                            ("types", TomlValue::Array(values)) => {
+                                for value in values.iter() {
+                                    match value {
+                                        TomlValue::String(s) => {
+                                            theme.dimmed_types.insert(s.clone());
+                                        },
+                                        _ => return Err(invalid_theme_error(String::from("dim.types: expected strings"))),
+                                    }
+                                }
+                            },
+                            ("types", _)     => return Err(invalid_theme_error(String::from("dim.types: expected array"))),
+                            _                => return Err(invalid_theme_error(format!("dim.{}: key unknown", key))),
+                        }
+                    }
+                },
+                "" => return Err(invalid_theme_error(format!("{}: key unknown", table.pairs[0].0))),
+                name => return Err(invalid_theme_error(format!("{}: table unknown", name))),
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Returns the style of a capacity, that is the style of the highest threshold that doesn't
+    /// exceed the capacity.
+    pub fn capacity_style(&self, percent: u64) -> Option<&str>
+    {
+        self.capacity_styles.iter().rev().find(|pair| pair.0 <= percent).map(|pair| pair.1.as_str())
+    }
+
+    /// Returns the style of a column.
+    pub fn column_style(&self, field: Field) -> Option<&str>
+    {
+        self.column_styles.iter().find(|pair| pair.0 == field).map(|pair| pair.1.as_str())
+    }
+}
@@ -0,0 +1,324 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::result;
+
+/// A value of a TOML subset.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TomlValue
+{
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<TomlValue>),
+}
+
+/// A table of a TOML document with pairs of keys and values in the order of the document.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TomlTable
+{
+    pub name: String,
+    pub pairs: Vec<(String, TomlValue)>,
+}
+
+/// A TOML document. The first table has an empty name and contains the pairs before the first
+/// table header.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TomlDocument
+{
+    pub tables: Vec<TomlTable>,
+}
+
+impl TomlDocument
+{
+    /// Returns the table that has the specified name.
+    pub fn table(&self, name: &str) -> Option<&TomlTable>
+    {
+        self.tables.iter().find(|table| table.name == name)
+    }
+}
+
+impl TomlTable
+{
+    /// Returns the value of the specified key.
+    pub fn get(&self, key: &str) -> Option<&TomlValue>
+    {
+        self.pairs.iter().find(|pair| pair.0 == key).map(|pair| &pair.1)
+    }
+}
+
+fn is_bare_key_char(c: char) -> bool
+{
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+struct TomlParser<'a>
+{
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> TomlParser<'a>
+{
+    fn skip_spaces(&mut self)
+    {
+        while let Some(' ') | Some('\t') = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    fn parse_key(&mut self) -> result::Result<String, String>
+    {
+        self.skip_spaces();
+        match self.chars.peek() {
+            Some('"') | Some('\'') => self.parse_string(),
+            _ => {
+                let mut key = String::new();
+                while let Some(c) = self.chars.peek() {
+                    if !is_bare_key_char(*c) {
+                        break;
+                    }
+                    key.push(*c);
+                    self.chars.next();
+                }
+                if key.is_empty() {
+                    Err(String::from("expected key"))
+                } else {
+                    Ok(key)
+                }
+            },
+        }
+    }
+
+    fn parse_string(&mut self) -> result::Result<String, String>
+    {
+        let quote = self.chars.next().unwrap();
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some(c) if c == quote => return Ok(s),
+                Some('\\') if quote == '"' => {
+                    match self.chars.next() {
+                        Some('n')  => s.push('\n'),
+                        Some('t')  => s.push('\t'),
+                        Some('r')  => s.push('\r'),
+                        Some('"')  => s.push('"'),
+                        Some('\\') => s.push('\\'),
+                        Some('u') => {
+                            let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                            match u32::from_str_radix(hex.as_str(), 16).ok().and_then(char::from_u32) {
+                                Some(c) => s.push(c),
+                                None    => return Err(String::from("invalid escape sequence")),
+                            }
+                        },
+                        _          => return Err(String::from("invalid escape sequence")),
+                    }
+                },
+                Some(c) => s.push(c),
+                None    => return Err(String::from("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> result::Result<TomlValue, String>
+    {
+        self.skip_spaces();
+        match self.chars.peek() {
+            Some('"') | Some('\'') => Ok(TomlValue::String(self.parse_string()?)),
+            Some('[') => {
+                self.chars.next();
+                let mut values: Vec<TomlValue> = Vec::new();
+                loop {
+                    self.skip_spaces();
+                    if self.chars.peek() == Some(&']') {
+                        self.chars.next();
+                        return Ok(TomlValue::Array(values));
+                    }
+                    values.push(self.parse_value()?);
+                    self.skip_spaces();
+                    match self.chars.next() {
+                        Some(',') => (),
+                        Some(']') => return Ok(TomlValue::Array(values)),
+                        _         => return Err(String::from("expected ',' or ']'")),
+                    }
+                }
+            },
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.peek() {
+                    if !is_bare_key_char(*c) && *c != '+' {
+                        break;
+                    }
+                    word.push(*c);
+                    self.chars.next();
+                }
+                match word.as_str() {
+                    "true"  => Ok(TomlValue::Boolean(true)),
+                    "false" => Ok(TomlValue::Boolean(false)),
+                    _ => {
+                        match word.replace('_', "").parse::<i64>() {
+                            Ok(x)  => Ok(TomlValue::Integer(x)),
+                            Err(_) => Err(format!("invalid value {:?}", word)),
+                        }
+                    },
+                }
+            },
+        }
+    }
+
+    fn expect_end(&mut self) -> result::Result<(), String>
+    {
+        self.skip_spaces();
+        match self.chars.next() {
+            None | Some('#') => Ok(()),
+            Some(c)          => Err(format!("unexpected character {:?}", c)),
+        }
+    }
+}
+
+/// Parses a document of a TOML subset.
+///
+/// The subset has tables, basic and literal strings, integers, booleans and arrays in one line.
+/// Dotted keys, inline tables, arrays of tables, floats and dates aren't supported.
+pub fn parse_toml(s: &str) -> result::Result<TomlDocument, String>
+{
+    let mut tables: Vec<TomlTable> = vec![TomlTable { name: String::new(), pairs: Vec::new(), }];
+    for (line_no, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parser = TomlParser { chars: line.chars().peekable(), };
+        let res = if line.starts_with('[') {
+            parser.chars.next();
+            parser.parse_key().and_then(|name| {
+                    parser.skip_spaces();
+                    if parser.chars.next() != Some(']') {
+                        return Err(String::from("expected ']'"));
+                    }
+                    if tables.iter().any(|table| table.name == name) {
+                        return Err(format!("table {:?} defined more than once", name));
+                    }
+                    tables.push(TomlTable { name, pairs: Vec::new(), });
+                    parser.expect_end()
+            })
+        } else {
+            parser.parse_key().and_then(|key| {
+                    parser.skip_spaces();
+                    if parser.chars.next() != Some('=') {
+                        return Err(String::from("expected '='"));
+                    }
+                    let value = parser.parse_value()?;
+                    let table = tables.last_mut().unwrap();
+                    if table.get(key.as_str()).is_some() {
+                        return Err(format!("key {:?} defined more than once", key));
+                    }
+                    table.pairs.push((key, value));
+                    parser.expect_end()
+            })
+        };
+        res.map_err(|err| format!("line {}: {}", line_no + 1, err))?;
+    }
+    Ok(TomlDocument { tables, })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn parse_value(s: &str) -> result::Result<TomlValue, String>
+    {
+        let doc = parse_toml(format!("a = {}", s).as_str())?;
+        Ok(doc.tables[0].get("a").unwrap().clone())
+    }
+
+    #[test]
+    fn test_parse_toml_parses_tables_and_pairs()
+    {
+        let doc = parse_toml("# comment\ntheme = \"dark\"\n\n[colors]\n  warning = 70 # percent\n\"quoted key\" = true\n['literal']\n").unwrap();
+        assert_eq!(3, doc.tables.len());
+        assert_eq!(Some(&TomlValue::String(String::from("dark"))), doc.table("").and_then(|table| table.get("theme")));
+        let colors = doc.table("colors").unwrap();
+        assert_eq!(Some(&TomlValue::Integer(70)), colors.get("warning"));
+        assert_eq!(Some(&TomlValue::Boolean(true)), colors.get("quoted key"));
+        assert!(doc.table("literal").unwrap().pairs.is_empty());
+        assert!(doc.table("none").is_none());
+    }
+
+    #[test]
+    fn test_parse_toml_parses_values()
+    {
+        assert_eq!(Ok(TomlValue::Integer(-1000000)), parse_value("-1_000_000"));
+        assert_eq!(Ok(TomlValue::Integer(5)), parse_value("+5"));
+        assert_eq!(Ok(TomlValue::Boolean(false)), parse_value("false"));
+        assert_eq!(Ok(TomlValue::String(String::from("a\"b\\\n\t\u{e9}"))), parse_value("\"a\\\"b\\\\\\n\\t\\u00e9\""));
+        assert_eq!(Ok(TomlValue::String(String::from("C:\\dir\\n"))), parse_value("'C:\\dir\\n'"));
+        assert_eq!(Ok(TomlValue::String(String::from("#"))), parse_value("\"#\" # comment"));
+        assert_eq!(Ok(TomlValue::Array(Vec::new())), parse_value("[ ]"));
+        assert_eq!(Ok(TomlValue::Array(vec![TomlValue::Integer(1), TomlValue::Array(vec![TomlValue::String(String::from("x"))])])), parse_value("[1, ['x'],]"));
+    }
+
+    #[test]
+    fn test_parse_toml_rejects_malformed_strings()
+    {
+        assert!(parse_value("\"abc").is_err());
+        assert!(parse_value("'abc").is_err());
+        assert!(parse_value("\"abc\\\"").is_err());
+        assert!(parse_value("\"\\x\"").is_err());
+        assert!(parse_value("\"\\u12\"").is_err());
+        assert!(parse_value("\"\\ud800\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_toml_rejects_malformed_values()
+    {
+        assert!(parse_value("").is_err());
+        assert!(parse_value("yes").is_err());
+        assert!(parse_value("1.5").is_err());
+        assert!(parse_value("9223372036854775808").is_err());
+        assert!(parse_value("1 2").is_err());
+        assert!(parse_value("[1, 2").is_err());
+        assert!(parse_value("[1 2]").is_err());
+        assert!(parse_value("{ b = 1 }").is_err());
+    }
+
+    #[test]
+    fn test_parse_toml_rejects_malformed_lines()
+    {
+        assert!(parse_toml("a").is_err());
+        assert!(parse_toml("a 1").is_err());
+        assert!(parse_toml("= 1").is_err());
+        assert!(parse_toml("a.b = 1").is_err());
+        assert!(parse_toml("[a").is_err());
+        assert!(parse_toml("[]").is_err());
+        assert!(parse_toml("[a] b").is_err());
+        assert!(parse_toml("[[a]]").is_err());
+    }
+
+    #[test]
+    fn test_parse_toml_rejects_duplicates()
+    {
+        assert!(parse_toml("a = 1\na = 2").is_err());
+        assert!(parse_toml("[a]\n[b]\n[a]").is_err());
+        assert!(parse_toml("a = 1\n[b]\na = 2").is_ok());
+    }
+
+    #[test]
+    fn test_parse_toml_reports_line_numbers()
+    {
+        assert_eq!(Err(String::from("line 3: unterminated string")), parse_toml("a = 1\n\nb = \"x"));
+    }
+}
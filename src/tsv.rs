@@ -0,0 +1,39 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+/// Escapes tabs, line breaks and backslashes in a TSV field with backslash sequences.
+pub fn tsv_field(s: &str) -> String
+{
+    let mut t = String::new();
+    for c in s.chars() {
+        match c {
+            '\t' => t.push_str("\\t"),
+            '\n' => t.push_str("\\n"),
+            '\r' => t.push_str("\\r"),
+            '\\' => t.push_str("\\\\"),
+            c    => t.push(c),
+        }
+    }
+    t
+}
+
+/// Formats fields as a TSV record without a line terminator.
+pub fn tsv_record<S: AsRef<str>>(fields: &[S]) -> String
+{
+    let escaped_fields: Vec<String> = fields.iter().map(|field| tsv_field(field.as_ref())).collect();
+    escaped_fields.join("\t")
+}
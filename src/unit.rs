@@ -0,0 +1,59 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use crate::size::*;
+
+/// A unit of sizes in the output.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SizeUnit
+{
+    /// Sizes are printed as numbers of blocks of the specified size.
+    Blocks(u64),
+    /// Sizes are printed in the human-readable form with the specified base.
+    Human(u64),
+}
+
+const BLOCK_SIZE_ENV_VARS: &[&str] = &["DF_BLOCK_SIZE", "BLOCK_SIZE", "BLOCKSIZE"];
+
+/// Parses a size unit as in the `--block-size` option.
+///
+/// Besides sizes, the `human-readable` and `si` words are accepted.
+pub fn parse_size_unit(s: &str) -> Option<SizeUnit>
+{
+    match s {
+        "human-readable" => Some(SizeUnit::Human(BINARY_BASE)),
+        "si"             => Some(SizeUnit::Human(SI_BASE)),
+        _ => {
+            match parse_size(s) {
+                Some(block_size) if block_size != 0 => Some(SizeUnit::Blocks(block_size)),
+                _                                   => None,
+            }
+        },
+    }
+}
+
+/// Resolves a size unit from the environment variables.
+///
+/// The `DF_BLOCK_SIZE`, `BLOCK_SIZE` and `BLOCKSIZE` variables are consulted in this order and
+/// invalid values are ignored. If none of these variables determines a unit and `POSIXLY_CORRECT`
+/// is set, 512-byte blocks are used as required by POSIX, like in GNU df. Otherwise `None` is
+/// returned.
+pub fn env_size_unit() -> Option<SizeUnit>
+{
+    BLOCK_SIZE_ENV_VARS.iter().find_map(|name| env::var(name).ok().and_then(|value| parse_size_unit(value.as_str())))
+        .or_else(|| env::var_os("POSIXLY_CORRECT").map(|_| SizeUnit::Blocks(512)))
+}
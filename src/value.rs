@@ -0,0 +1,61 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+
+use std::fmt;
+
+/// A raw value of a field for structured output formats.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Value
+{
+    Null,
+    Integer(i128),
+    String(String),
+}
+
+impl Value
+{
+    /// Creates a string value or a null value if there is no string.
+    pub fn from_option_string(s: Option<&String>) -> Value
+    {
+        match s {
+            Some(s) => Value::String(s.clone()),
+            None    => Value::Null,
+        }
+    }
+
+    /// Creates an integer value or a null value if there is no integer.
+    pub fn from_option_integer<T: Into<i128>>(x: Option<T>) -> Value
+    {
+        match x {
+            Some(x) => Value::Integer(x.into()),
+            None    => Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for Value
+{
+    /// Formats the value as plain text. A null value is an empty string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            Value::Null       => Ok(()),
+            Value::Integer(x) => write!(f, "{}", x),
+            Value::String(s)  => write!(f, "{}", s),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use crate::value::*;
+
+/// Escapes a string for an XML attribute value. Characters which aren't allowed in XML are
+/// replaced by the replacement character.
+pub fn xml_attribute_value(s: &str) -> String
+{
+    let mut t = String::new();
+    for c in s.chars() {
+        match c {
+            '&'  => t.push_str("&amp;"),
+            '<'  => t.push_str("&lt;"),
+            '>'  => t.push_str("&gt;"),
+            '"'  => t.push_str("&quot;"),
+            '\t' => t.push_str("&#9;"),
+            '\n' => t.push_str("&#10;"),
+            '\r' => t.push_str("&#13;"),
+            c if (c as u32) < 0x20 => t.push('\u{fffd}'),
+            c    => t.push(c),
+        }
+    }
+    t
+}
+
+/// Formats pairs of names and values as an empty XML element with attributes. Attributes which
+/// have null values are omitted.
+pub fn xml_empty_element(name: &str, pairs: &[(&str, Value)]) -> String
+{
+    let mut s = format!("<{}", name);
+    for (attr_name, value) in pairs.iter() {
+        if *value != Value::Null {
+            s.push_str(format!(" {}=\"{}\"", attr_name, xml_attribute_value(value.to_string().as_str())).as_str());
+        }
+    }
+    s.push_str("/>");
+    s
+}
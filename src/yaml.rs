@@ -0,0 +1,133 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use crate::json::json_string;
+use crate::value::*;
+
+const YAML_KEYWORDS: &[&str] = &[
+    "null", "Null", "NULL", "~",
+    "true", "True", "TRUE", "false", "False", "FALSE",
+    "yes", "Yes", "YES", "no", "No", "NO",
+    "on", "On", "ON", "off", "Off", "OFF",
+    ".inf", ".Inf", ".INF", "+.inf", "+.Inf", "+.INF", ".nan", ".NaN", ".NAN",
+];
+
+fn is_plain_yaml_string(s: &str) -> bool
+{
+    !s.is_empty() &&
+    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '/' || c == '.' || c == '_' || c == '-' || c == '+' || c == ':') &&
+    !s.starts_with('-') && !s.ends_with(':') && !s.contains(": ") &&
+    !YAML_KEYWORDS.contains(&s) &&
+    s.parse::<f64>().is_err() && !s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Formats a value as a YAML scalar. A string is double-quoted if it could be read as another
+/// type or it contains special characters.
+pub fn yaml_scalar(value: &Value) -> String
+{
+    match value {
+        Value::Null       => String::from("null"),
+        Value::Integer(x) => format!("{}", x),
+        Value::String(s)  => {
+            if is_plain_yaml_string(s.as_str()) {
+                s.clone()
+            } else {
+                // Double-quoted YAML strings have the same escape sequences as JSON strings.
+                json_string(s.as_str())
+            }
+        },
+    }
+}
+
+/// Formats pairs of names and values as a YAML mapping which is an item of a block sequence.
+pub fn yaml_sequence_item(pairs: &[(&str, Value)]) -> String
+{
+    let mut s = String::new();
+    for (i, (name, value)) in pairs.iter().enumerate() {
+        s.push_str(if i == 0 { "- " } else { "  " });
+        s.push_str(format!("{}: {}\n", name, yaml_scalar(value)).as_str());
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn string_scalar(s: &str) -> String
+    {
+        yaml_scalar(&Value::String(String::from(s)))
+    }
+
+    #[test]
+    fn test_yaml_scalar_formats_null_and_integers()
+    {
+        assert_eq!("null", yaml_scalar(&Value::Null));
+        assert_eq!("-42", yaml_scalar(&Value::Integer(-42)));
+    }
+
+    #[test]
+    fn test_yaml_scalar_keeps_plain_strings()
+    {
+        assert_eq!("/dev/sda1", string_scalar("/dev/sda1"));
+        assert_eq!("ext4", string_scalar("ext4"));
+        assert_eq!("a:b", string_scalar("a:b"));
+        assert_eq!(".config", string_scalar(".config"));
+    }
+
+    #[test]
+    fn test_yaml_scalar_quotes_strings_read_as_other_types()
+    {
+        assert_eq!("\"\"", string_scalar(""));
+        assert_eq!("\"null\"", string_scalar("null"));
+        assert_eq!("\"~\"", string_scalar("~"));
+        assert_eq!("\"yes\"", string_scalar("yes"));
+        assert_eq!("\"Off\"", string_scalar("Off"));
+        assert_eq!("\"12\"", string_scalar("12"));
+        assert_eq!("\"1e3\"", string_scalar("1e3"));
+        assert_eq!("\"0x1f\"", string_scalar("0x1f"));
+        assert_eq!("\"inf\"", string_scalar("inf"));
+    }
+
+    #[test]
+    fn test_yaml_scalar_quotes_special_floats()
+    {
+        assert_eq!("\".inf\"", string_scalar(".inf"));
+        assert_eq!("\".Inf\"", string_scalar(".Inf"));
+        assert_eq!("\"+.INF\"", string_scalar("+.INF"));
+        assert_eq!("\"-.inf\"", string_scalar("-.inf"));
+        assert_eq!("\".nan\"", string_scalar(".nan"));
+        assert_eq!("\".NaN\"", string_scalar(".NaN"));
+    }
+
+    #[test]
+    fn test_yaml_scalar_quotes_special_characters()
+    {
+        assert_eq!("\"Mounted on\"", string_scalar("Mounted on"));
+        assert_eq!("\"-x\"", string_scalar("-x"));
+        assert_eq!("\"a:\"", string_scalar("a:"));
+        assert_eq!("\"#a\"", string_scalar("#a"));
+        assert_eq!("\"a\\\"b\\nc\"", string_scalar("a\"b\nc"));
+        assert_eq!("\"'a'\"", string_scalar("'a'"));
+    }
+
+    #[test]
+    fn test_yaml_sequence_item()
+    {
+        assert_eq!("- target: /\n  size: 1\n", yaml_sequence_item(&[("target", Value::String(String::from("/"))), ("size", Value::Integer(1))]));
+    }
+}
@@ -0,0 +1,62 @@
+//
+// Mntdf - Df program with mnt crate.
+// Copyright (C) 2022-2023 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use crate::size::Rounding;
+
+/// A mode of a filesystem item value as in the `vfs.fs.size` and `vfs.fs.inode` items of the
+/// Zabbix agent.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ZabbixMode
+{
+    Total,
+    Free,
+    Used,
+    PFree,
+    PUsed,
+}
+
+impl ZabbixMode
+{
+    /// Returns the mode that has the specified name.
+    pub fn from_name(name: &str) -> Option<ZabbixMode>
+    {
+        match name {
+            "total" => Some(ZabbixMode::Total),
+            "free"  => Some(ZabbixMode::Free),
+            "used"  => Some(ZabbixMode::Used),
+            "pfree" => Some(ZabbixMode::PFree),
+            "pused" => Some(ZabbixMode::PUsed),
+            _       => None,
+        }
+    }
+}
+
+/// Formats the value of the mode from the total, used and available amounts. Free amounts are
+/// available to unprivileged users as in the Zabbix agent. Percentages are relative to the sum of
+/// used and available amounts and have two decimal digits.
+pub fn zabbix_value(mode: ZabbixMode, total: u64, used: u64, available: i128) -> String
+{
+    let available = available.max(0) as u128;
+    let sum = u128::from(used) + available;
+    let free_hundredths = if sum != 0 { Rounding::Nearest.div(available * 10000, sum) } else { 10000 };
+    match mode {
+        ZabbixMode::Total => format!("{}", total),
+        ZabbixMode::Free  => format!("{}", available),
+        ZabbixMode::Used  => format!("{}", used),
+        ZabbixMode::PFree => format!("{}.{:02}", free_hundredths / 100, free_hundredths % 100),
+        ZabbixMode::PUsed => format!("{}.{:02}", (10000 - free_hundredths) / 100, (10000 - free_hundredths) % 100),
+    }
+}